@@ -0,0 +1,320 @@
+//! Achievements
+//!
+//! These are unlocked once per profile and kept for good, in a file next to
+//! the profile's career:
+//!
+//!    wizcasn-achievements 1
+//!    unlocked vendor-killer 1760000000
+use crate::G;
+use crate::frontend::*;
+use crate::scores::Ending;
+
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use wizardscastle::player::Race;
+
+const MAGIC: &str = "wizcasn-achievements 1";
+
+/// There are this many treasures in the castle
+const TREASURES: u32 = 8;
+
+/// A win in fewer turns than this is a fast one
+const FAST_WIN_TURNS: u32 = 300;
+
+/// Something worth doing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Achievement {
+    DragonDeathspell,
+    AllTreasures,
+    FastWin,
+    VendorKiller,
+    OpalEye,
+    WonHobbit,
+    WonElf,
+    WonHuman,
+    WonDwarf,
+}
+
+/// All of them, in the order they're listed
+pub const ACHIEVEMENTS: [Achievement; 9] = [
+    Achievement::DragonDeathspell,
+    Achievement::AllTreasures,
+    Achievement::FastWin,
+    Achievement::VendorKiller,
+    Achievement::OpalEye,
+    Achievement::WonHobbit,
+    Achievement::WonElf,
+    Achievement::WonHuman,
+    Achievement::WonDwarf,
+];
+
+impl Achievement {
+    /// What it's called in the file
+    fn id(self) -> &'static str {
+        match self {
+            Achievement::DragonDeathspell => "dragon-deathspell",
+            Achievement::AllTreasures => "all-treasures",
+            Achievement::FastWin => "fast-win",
+            Achievement::VendorKiller => "vendor-killer",
+            Achievement::OpalEye => "opal-eye",
+            Achievement::WonHobbit => "won-hobbit",
+            Achievement::WonElf => "won-elf",
+            Achievement::WonHuman => "won-human",
+            Achievement::WonDwarf => "won-dwarf",
+        }
+    }
+
+    fn from_id(id: &str) -> Option<Achievement> {
+        ACHIEVEMENTS.into_iter().find(|a| a.id() == id)
+    }
+
+    /// What it's called on the screen
+    pub fn title(self) -> &'static str {
+        match self {
+            Achievement::DragonDeathspell => "Dragonbane",
+            Achievement::AllTreasures => "Hoarder",
+            Achievement::FastWin => "In and Out",
+            Achievement::VendorKiller => "No Refunds",
+            Achievement::OpalEye => "Seeing Clearly",
+            Achievement::WonHobbit => "Small but Mighty",
+            Achievement::WonElf => "Elven Triumph",
+            Achievement::WonHuman => "Only Human",
+            Achievement::WonDwarf => "Stout Heart",
+        }
+    }
+
+    /// How to get it
+    pub fn how(self) -> String {
+        match self {
+            Achievement::DragonDeathspell => String::from("Kill a dragon with a deathspell"),
+            Achievement::AllTreasures => String::from("Find all the treasures in one game"),
+            Achievement::FastWin => {
+                format!("Escape with the Orb in under {} turns", FAST_WIN_TURNS)
+            }
+            Achievement::VendorKiller => String::from("Kill a vendor"),
+            Achievement::OpalEye => String::from("Be cured of blindness by the Opal Eye"),
+            Achievement::WonHobbit => String::from("Win as a Hobbit"),
+            Achievement::WonElf => String::from("Win as an Elf"),
+            Achievement::WonHuman => String::from("Win as a Human"),
+            Achievement::WonDwarf => String::from("Win as a Dwarf"),
+        }
+    }
+
+    /// The one for winning as a race
+    fn won_as(race: Race) -> Achievement {
+        match race {
+            Race::Hobbit => Achievement::WonHobbit,
+            Race::Elf => Achievement::WonElf,
+            Race::Human => Achievement::WonHuman,
+            Race::Dwarf => Achievement::WonDwarf,
+        }
+    }
+}
+
+/// Read the unlocked achievements, and when each was unlocked
+fn read_unlocked(f: &mut File) -> io::Result<BTreeMap<&'static str, u64>> {
+    let mut text = String::new();
+
+    f.read_to_string(&mut text)?;
+
+    Ok(text
+        .lines()
+        .filter_map(
+            |line| match line.split_whitespace().collect::<Vec<_>>()[..] {
+                ["unlocked", id, date] => {
+                    Some((Achievement::from_id(id)?.id(), date.parse().ok()?))
+                }
+                _ => None,
+            },
+        )
+        .collect())
+}
+
+impl G {
+    /// Where the profile's achievements are kept
+    fn achievements_path(&self) -> Option<PathBuf> {
+        self.career_path().map(|p| p.with_extension("achievements"))
+    }
+
+    /// Read which achievements the profile has, and when it got them
+    pub fn load_achievements(&self) -> BTreeMap<&'static str, u64> {
+        let Some(path) = self.achievements_path() else {
+            return BTreeMap::new();
+        };
+
+        let Ok(mut f) = File::open(path) else {
+            return BTreeMap::new();
+        };
+
+        if f.lock_shared().is_err() {
+            return BTreeMap::new();
+        }
+
+        read_unlocked(&mut f).unwrap_or_default()
+    }
+
+    /// Unlock an achievement, and say so if it's new
+    ///
+    /// Games that aren't scored don't earn any.
+    pub fn achieve(&self, a: Achievement) {
+        if !self.game_counts() {
+            return;
+        }
+
+        let Some(path) = self.achievements_path() else {
+            return;
+        };
+
+        let result = (|| {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+
+            let mut f = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(false)
+                .open(&path)?;
+
+            f.lock()?;
+
+            let mut unlocked = read_unlocked(&mut f)?;
+
+            if unlocked.contains_key(a.id()) {
+                return Ok(false);
+            }
+
+            let date = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs());
+
+            unlocked.insert(a.id(), date);
+
+            let mut text = format!("{}\n", MAGIC);
+
+            for (id, date) in &unlocked {
+                text.push_str(&format!("unlocked {} {}\n", id, date));
+            }
+
+            f.seek(SeekFrom::Start(0))?;
+            f.set_len(0)?;
+            f.write_all(text.as_bytes())?;
+
+            Ok::<_, io::Error>(true)
+        })();
+
+        match result {
+            Ok(true) => self.achievement_popup(a),
+            Ok(false) => (),
+            Err(err) => self.popup_error(&format!(
+                "Couldn't save your achievement in {}: {}",
+                path.display(),
+                err
+            )),
+        }
+    }
+
+    /// Check for the achievements that come from finding a treasure
+    pub fn treasure_achievements(&self) {
+        if self.tally.treasures() == TREASURES {
+            self.achieve(Achievement::AllTreasures);
+        }
+    }
+
+    /// Check for the achievements that come from how the game ended
+    pub fn ending_achievements(&self, ending: Ending) {
+        if ending != Ending::Won {
+            return;
+        }
+
+        if *self.game.turn() < FAST_WIN_TURNS {
+            self.achieve(Achievement::FastWin);
+        }
+
+        self.achieve(Achievement::won_as(self.game.player_race()));
+    }
+
+    /// Announce a new achievement
+    fn achievement_popup(&self, a: Achievement) {
+        let how = a.how();
+        let width = (how.len() as i32 + 10).max(36);
+
+        let w = self.popup(10, width + width % 2);
+
+        self.wcon(w, G::A_TITLE());
+        self.mvwprintw_center(w, 2, "Achievement unlocked!");
+        self.wcoff(w, G::A_TITLE());
+
+        self.wcon(w, "bold-yellow");
+        self.mvwprintw_center(w, 4, a.title());
+        self.wcoff(w, "bold-yellow");
+
+        self.mvwprintw_center(w, 5, &how);
+
+        self.ui.attr_on(w, A_REVERSE);
+        self.mvwprintw_center_notrim(w, 7, " Press any key ");
+        self.ui.attr_off(w, A_REVERSE);
+
+        self.ui.border(w);
+        self.ui.refresh(w);
+
+        self.getkey();
+
+        self.popup_close(w);
+
+        self.redraw_underwins();
+    }
+
+    /// List all the achievements, unlocked or not
+    pub fn achievements_screen(&self) {
+        let unlocked = self.load_achievements();
+
+        let w = self.popup(ACHIEVEMENTS.len() as i32 + 9, 64);
+
+        self.wcon(w, G::A_TITLE());
+        self.mvwprintw_center(
+            w,
+            2,
+            &format!(
+                "Achievements ({} of {})",
+                unlocked.len(),
+                ACHIEVEMENTS.len()
+            ),
+        );
+        self.wcoff(w, G::A_TITLE());
+
+        for (i, a) in ACHIEVEMENTS.iter().enumerate() {
+            let y = 4 + i as i32;
+
+            if unlocked.contains_key(a.id()) {
+                self.wcon(w, "bold-yellow");
+                self.ui.mvaddstr(w, y, 3, &format!("* {:<17}", a.title()));
+                self.wcoff(w, "bold-yellow");
+            } else {
+                self.ui.attr_on(w, A_DIM);
+                self.ui.mvaddstr(w, y, 3, &format!("  {:<17}", a.title()));
+                self.ui.attr_off(w, A_DIM);
+            }
+
+            self.ui.addstr(w, &a.how());
+        }
+
+        self.ui.attr_on(w, A_REVERSE);
+        self.mvwprintw_center_notrim(w, ACHIEVEMENTS.len() as i32 + 6, " Press any key ");
+        self.ui.attr_off(w, A_REVERSE);
+
+        self.ui.border(w);
+        self.ui.refresh(w);
+
+        self.getkey();
+
+        self.popup_close(w);
+
+        self.redraw_underwins();
+    }
+}
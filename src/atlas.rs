@@ -0,0 +1,128 @@
+//! The atlas: every level of the castle at once, on terminals wide enough
+//! to have room for it to the right of the usual screen
+use crate::G;
+use crate::frontend::*;
+
+use wizardscastle::room::RoomType;
+
+/// Columns between mini-maps, and lines between rows of them
+const GAP_COLS: i32 = 2;
+const GAP_LINES: i32 = 1;
+
+impl G {
+    /// How many levels go across an atlas window of the given size, or
+    /// `None` if they don't all fit
+    pub fn atlas_across(&self, lines: i32, cols: i32) -> Option<i32> {
+        let (xs, ys, zs) = self.castle_size();
+        let (xs, ys, zs) = (xs as i32, ys as i32, zs as i32);
+
+        // Inside the border, with a space on each side
+        let width = cols - 4;
+        let height = lines - 2;
+
+        let across = ((width + GAP_COLS) / (xs + GAP_COLS)).min(zs);
+
+        if across < 1 {
+            return None;
+        }
+
+        let down = (zs + across - 1) / across;
+
+        // Each mini-map has a label above it
+        (down * (ys + 1) + (down - 1) * GAP_LINES <= height).then_some(across)
+    }
+
+    /// Draw the atlas, if there's room for one
+    pub fn update_atlas(&self, show_all: bool) {
+        let Some(w) = self.atlaswin.get() else {
+            return;
+        };
+
+        let Some(across) = self.atlas_across(self.ui.lines(), self.ui.maxx(w)) else {
+            return;
+        };
+
+        let (xs, ys, zs) = self.castle_size();
+
+        self.ui.erase(w);
+
+        for z in 0..zs {
+            let top = 1 + (z as i32 / across) * (ys as i32 + 1 + GAP_LINES);
+            let left = 2 + (z as i32 % across) * (xs as i32 + GAP_COLS);
+
+            let here = z == self.game.player_z();
+
+            let label_attr = if here { A_BOLD | A_REVERSE } else { A_BOLD };
+
+            self.ui.attr_on(w, label_attr);
+            self.ui.mvaddstr(w, top, left, &format!("Level {}", z + 1));
+            self.ui.attr_off(w, label_attr);
+
+            for y in 0..ys {
+                self.ui.mv(w, top + 1 + y as i32, left);
+
+                for x in 0..xs {
+                    self.draw_atlas_room(w, x, y, z, show_all);
+                }
+            }
+        }
+
+        self.ui.border(w);
+        self.ui.mvaddstr(w, 0, 2, " Atlas ");
+
+        self.ui.refresh(w);
+    }
+
+    /// Draw one room of the atlas at the cursor
+    ///
+    /// The player shows as `@`, and stairs are in reverse video so routes
+    /// between levels stand out.
+    fn draw_atlas_room(&self, w: Win, x: u32, y: u32, z: u32, show_all: bool) {
+        if self.game.player_is_blind() {
+            self.ui.addstr(w, "-");
+            return;
+        }
+
+        if x == self.game.player_x() && y == self.game.player_y() && z == self.game.player_z() {
+            self.ui.attr_on(w, A_BOLD);
+            self.ui.addstr(w, "@");
+            self.ui.attr_off(w, A_BOLD);
+            return;
+        }
+
+        let r = self.game.dungeon_room_at(x, y, z);
+
+        if !(r.discovered || show_all) {
+            if let Some(s) = self.rumor_at(x, y, z) {
+                self.wcon(w, "rumor");
+                self.ui.addch(w, s.glyph());
+                self.wcoff(w, "rumor");
+                return;
+            }
+
+            self.ui.attr_on(w, A_DIM);
+            self.ui.addstr(w, "?");
+            self.ui.attr_off(w, A_DIM);
+            return;
+        }
+
+        let room_ch = G::room_char(&r.roomtype);
+        let attr_str = format!("room-{}", room_ch);
+
+        let stairs = matches!(r.roomtype, RoomType::StairsUp | RoomType::StairsDown);
+
+        self.wcon(w, &attr_str);
+
+        if stairs {
+            self.ui.attr_on(w, A_REVERSE);
+        }
+
+        self.ui.addstr(w, &format!("{}", room_ch));
+
+        if stairs {
+            self.ui.attr_off(w, A_REVERSE);
+        }
+
+        self.wcoff(w, &attr_str);
+    }
+}
@@ -0,0 +1,263 @@
+//! The bestiary, and the cookbook that comes with it
+//!
+//! Every monster the player runs into is written up here: how often they've
+//! met, how those meetings went, and what got cooked afterwards. Like the
+//! career, it's kept per profile and filled in over many games.
+//!
+//!    wizcasn-bestiary 1
+//!    beast orc 3 2 0 1 40 12
+//!    dish orc stew
+//!
+//! A beast line has how many were met, killed, fled from, and bribed, then
+//! the damage dealt to them and taken from them.
+use crate::G;
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+
+use wizardscastle::monster::MonsterType;
+
+const MAGIC: &str = "wizcasn-bestiary 1";
+
+/// Every kind of monster, in the order they're listed
+pub const MONSTERS: [MonsterType; 13] = [
+    MonsterType::Kobold,
+    MonsterType::Orc,
+    MonsterType::Wolf,
+    MonsterType::Goblin,
+    MonsterType::Ogre,
+    MonsterType::Troll,
+    MonsterType::Bear,
+    MonsterType::Minotaur,
+    MonsterType::Gargoyle,
+    MonsterType::Chimera,
+    MonsterType::Balrog,
+    MonsterType::Dragon,
+    MonsterType::Vendor,
+];
+
+/// What a dead monster can be cooked into, after its name
+pub const DISHES: [&str; 8] = [
+    "wich", " stew", " soup", " burger", " roast", " munchy", " taco", " pie",
+];
+
+/// What's known about one kind of monster
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Beast {
+    pub met: u32,
+    pub kills: u32,
+    pub retreats: u32,
+    pub bribes: u32,
+    pub dealt: u64,
+    pub taken: u64,
+}
+
+/// What's known about all of them, by name, and the dishes made of them
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Bestiary {
+    pub beasts: BTreeMap<String, Beast>,
+    pub dishes: BTreeSet<String>,
+}
+
+impl Bestiary {
+    /// The entry for a monster, started if there isn't one yet
+    pub fn entry(&mut self, name: &str) -> &mut Beast {
+        self.beasts.entry(name.to_string()).or_default()
+    }
+
+    /// Read a bestiary from the file's text
+    pub fn parse(text: &str) -> Bestiary {
+        let mut b = Bestiary::default();
+
+        for line in text.lines() {
+            let words: Vec<&str> = line.split_whitespace().collect();
+
+            let num = |s: &str| s.parse::<u64>().unwrap_or(0);
+
+            match words.as_slice() {
+                ["beast", name, met, kills, retreats, bribes, dealt, taken] => {
+                    b.beasts.insert(
+                        name.to_string(),
+                        Beast {
+                            met: num(met) as u32,
+                            kills: num(kills) as u32,
+                            retreats: num(retreats) as u32,
+                            bribes: num(bribes) as u32,
+                            dealt: num(dealt),
+                            taken: num(taken),
+                        },
+                    );
+                }
+                ["dish", dish @ ..] if !dish.is_empty() => {
+                    b.dishes.insert(dish.join(" "));
+                }
+                _ => (),
+            }
+        }
+
+        b
+    }
+
+    /// Write a bestiary out as the file's text
+    pub fn to_text(&self) -> String {
+        let mut text = format!("{}\n", MAGIC);
+
+        for (name, b) in &self.beasts {
+            text.push_str(&format!(
+                "beast {} {} {} {} {} {} {}\n",
+                name, b.met, b.kills, b.retreats, b.bribes, b.dealt, b.taken
+            ));
+        }
+
+        for dish in &self.dishes {
+            text.push_str(&format!("dish {}\n", dish));
+        }
+
+        text
+    }
+
+    /// Add what another bestiary knows to this one
+    pub fn add(&mut self, other: &Bestiary) {
+        for (name, o) in &other.beasts {
+            let b = self.entry(name);
+
+            b.met += o.met;
+            b.kills += o.kills;
+            b.retreats += o.retreats;
+            b.bribes += o.bribes;
+            b.dealt += o.dealt;
+            b.taken += o.taken;
+        }
+
+        self.dishes.extend(other.dishes.iter().cloned());
+    }
+}
+
+impl G {
+    /// Where the profile's bestiary is kept
+    fn bestiary_path(&self) -> Option<PathBuf> {
+        self.career_path().map(|p| p.with_extension("bestiary"))
+    }
+
+    /// Read the profile's bestiary
+    pub fn load_bestiary(&self) -> Bestiary {
+        let Some(path) = self.bestiary_path() else {
+            return Bestiary::default();
+        };
+
+        let Ok(mut f) = File::open(path) else {
+            return Bestiary::default();
+        };
+
+        let mut text = String::new();
+
+        if f.lock_shared().is_err() || f.read_to_string(&mut text).is_err() {
+            return Bestiary::default();
+        }
+
+        Bestiary::parse(&text)
+    }
+
+    /// Add what was learned about monsters this game to the profile's
+    /// bestiary
+    ///
+    /// It's only kept if the game counts, but it's forgotten either way, so
+    /// the bestiary doesn't show it twice.
+    pub fn record_bestiary(&mut self) {
+        let beasts = std::mem::take(&mut self.beasts);
+
+        if !self.game_counts() {
+            return;
+        }
+
+        let Some(path) = self.bestiary_path() else {
+            return;
+        };
+
+        let result = (|| {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+
+            let mut f = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(false)
+                .open(&path)?;
+
+            f.lock()?;
+
+            let mut text = String::new();
+            f.read_to_string(&mut text)?;
+
+            let mut b = Bestiary::parse(&text);
+            b.add(&beasts);
+
+            f.seek(SeekFrom::Start(0))?;
+            f.set_len(0)?;
+            f.write_all(b.to_text().as_bytes())?;
+
+            Ok::<_, io::Error>(())
+        })();
+
+        if let Err(err) = result {
+            self.popup_error(&format!(
+                "Couldn't save your bestiary in {}: {}",
+                path.display(),
+                err
+            ));
+        }
+    }
+
+    /// Show the bestiary and cookbook, with this game's monsters in it too
+    pub fn bestiary_popup(&self) {
+        let mut b = self.load_bestiary();
+        b.add(&self.beasts);
+
+        let mut rows = Vec::new();
+
+        if !b.beasts.is_empty() {
+            rows.push(format!(
+                "{:<10}{:>6}{:>7}{:>6}{:>8}{:>8}{:>8}",
+                "", "Met", "Killed", "Fled", "Bribed", "Dealt", "Taken"
+            ));
+
+            for m in MONSTERS {
+                let name = G::monster_name(m);
+
+                if let Some(e) = b.beasts.get(&name) {
+                    rows.push(format!(
+                        "{:<10}{:>6}{:>7}{:>6}{:>8}{:>8}{:>8}",
+                        G::initial_upper(&name),
+                        e.met,
+                        e.kills,
+                        e.retreats,
+                        e.bribes,
+                        e.dealt,
+                        e.taken
+                    ));
+                }
+            }
+
+            rows.push(String::new());
+            rows.push(format!(
+                "Cookbook: {} of {} dishes",
+                b.dishes.len(),
+                MONSTERS.len() * DISHES.len()
+            ));
+
+            for dish in &b.dishes {
+                rows.push(format!("  {}", G::initial_upper(dish)));
+            }
+        }
+
+        self.list_popup(
+            &format!("Bestiary: {} of {} met", b.beasts.len(), MONSTERS.len()),
+            "You haven't met any monsters yet.",
+            &rows,
+        );
+    }
+}
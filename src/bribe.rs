@@ -1,12 +1,12 @@
 use crate::G;
-use ncurses::*;
+use crate::frontend::*;
 
 impl G {
     /// Bribe a monster
     pub fn combat_bribe(&mut self) -> bool {
         let mut bribed = false;
 
-        let w = G::popup(9, 33);
+        let w = self.popup(9, 33);
 
         if let Ok(Some(t_type)) = self.game.bribe_proposition() {
             let tname = G::treasure_name(t_type);
@@ -19,14 +19,14 @@ impl G {
 
             self.wprintw_center(w, "|[Y]|es   |[N]|o");
 
-            box_(w, 0, 0);
+            self.ui.border(w);
 
-            wrefresh(w);
+            self.ui.refresh(w);
 
             let mut done = false;
 
             while !done {
-                match G::norm_key(getch()) {
+                match G::norm_key(self.getkey()) {
                     'Y' => {
                         match self.game.bribe_accept() {
                             Ok(_) => {
@@ -58,28 +58,28 @@ impl G {
             panic!("We should have been able to bribe at this point");
         }
 
-        G::popup_close(w);
+        self.popup_close(w);
 
         self.redraw_underwins();
 
         if bribed {
-            let w = G::popup(7, 37);
+            let w = self.popup(7, 37);
 
             self.wcon(w, G::A_TITLE());
             self.mvwprintw_center(w, 2, "Ok, just don't tell anyone.\n\n");
             self.wcoff(w, G::A_TITLE());
 
-            wattr_on(w, A_REVERSE);
+            self.ui.attr_on(w, A_REVERSE);
             self.wprintw_center_notrim(w, " Press any key ");
-            wattr_off(w, A_REVERSE);
+            self.ui.attr_off(w, A_REVERSE);
 
-            box_(w, 0, 0);
+            self.ui.border(w);
 
-            wrefresh(w);
+            self.ui.refresh(w);
 
-            getch();
+            self.getkey();
 
-            G::popup_close(w);
+            self.popup_close(w);
 
             self.redraw_underwins();
         }
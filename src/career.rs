@@ -0,0 +1,349 @@
+//! Career statistics
+//!
+//! Each profile keeps a tally of every game it's played, in a text file in
+//! the player's data directory. Like the score file, it's locked while it's
+//! read or written.
+//!
+//!    wizcasn-career 1
+//!    games 12
+//!    died strength 4
+//!    killed 7 goblin
+//!
+//! Lines the game doesn't know are skipped, so older versions can read
+//! newer files.
+use crate::G;
+use crate::frontend::*;
+use crate::scores::Ending;
+
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+
+use wizardscastle::player::Stat;
+
+const MAGIC: &str = "wizcasn-career 1";
+
+/// The profile used if none is given
+const DEFAULT_PROFILE: &str = "default";
+
+/// What happened in the game being played, for adding to the career
+#[derive(Debug, Clone, Default)]
+pub struct Tally {
+    kills: BTreeMap<String, u32>,
+    vendors: u32,
+    treasures: u32,
+    gold: u64,
+}
+
+/// Everything a profile has done, over all its games
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Career {
+    pub games: u32,
+    pub won: u32,
+    pub escaped: u32,
+    pub quit: u32,
+    pub died: BTreeMap<String, u32>,
+    pub turns: u64,
+    pub treasures: u64,
+    pub gold: u64,
+    pub vendors: u32,
+    pub kills: BTreeMap<String, u32>,
+}
+
+impl Tally {
+    /// Count a monster killed, and the hoard it had
+    pub fn kill(&mut self, name: &str, vendor: bool, hoard: u32) {
+        if vendor {
+            self.vendors += 1;
+        } else {
+            *self.kills.entry(name.to_string()).or_default() += 1;
+            self.gold += hoard as u64;
+        }
+    }
+
+    /// Count a treasure found
+    pub fn treasure(&mut self) {
+        self.treasures += 1;
+    }
+
+    /// How many treasures have been found
+    pub fn treasures(&self) -> u32 {
+        self.treasures
+    }
+
+    /// Count gold found or got from selling
+    pub fn gold(&mut self, amount: u32) {
+        self.gold += amount as u64;
+    }
+}
+
+impl Career {
+    /// Read a career from the file's text
+    pub fn parse(text: &str) -> Career {
+        let mut c = Career::default();
+
+        for line in text.lines() {
+            let words: Vec<&str> = line.split_whitespace().collect();
+
+            let num = |s: &str| s.parse::<u64>().unwrap_or(0);
+
+            match words.as_slice() {
+                ["games", n] => c.games = num(n) as u32,
+                ["won", n] => c.won = num(n) as u32,
+                ["escaped", n] => c.escaped = num(n) as u32,
+                ["quit", n] => c.quit = num(n) as u32,
+                ["died", cause, n] => {
+                    c.died.insert(cause.to_string(), num(n) as u32);
+                }
+                ["turns", n] => c.turns = num(n),
+                ["treasures", n] => c.treasures = num(n),
+                ["gold", n] => c.gold = num(n),
+                ["vendors", n] => c.vendors = num(n) as u32,
+                ["killed", n, name @ ..] if !name.is_empty() => {
+                    c.kills.insert(name.join(" "), num(n) as u32);
+                }
+                _ => (),
+            }
+        }
+
+        c
+    }
+
+    /// Write a career out as the file's text
+    pub fn to_text(&self) -> String {
+        let mut text = format!(
+            "{}\ngames {}\nwon {}\nescaped {}\nquit {}\n",
+            MAGIC, self.games, self.won, self.escaped, self.quit
+        );
+
+        for (cause, n) in &self.died {
+            text.push_str(&format!("died {} {}\n", cause, n));
+        }
+
+        text.push_str(&format!(
+            "turns {}\ntreasures {}\ngold {}\nvendors {}\n",
+            self.turns, self.treasures, self.gold, self.vendors
+        ));
+
+        for (name, n) in &self.kills {
+            text.push_str(&format!("killed {} {}\n", n, name));
+        }
+
+        text
+    }
+
+    /// Add a game that just ended
+    pub fn add(&mut self, ending: Ending, cause: Option<&str>, turns: u32, tally: &Tally) {
+        self.games += 1;
+
+        match ending {
+            Ending::Won => self.won += 1,
+            Ending::Escaped => self.escaped += 1,
+            Ending::Quit => self.quit += 1,
+            Ending::Died => {
+                *self
+                    .died
+                    .entry(cause.unwrap_or("other").to_string())
+                    .or_default() += 1
+            }
+        }
+
+        self.turns += turns as u64;
+        self.treasures += tally.treasures as u64;
+        self.gold += tally.gold;
+        self.vendors += tally.vendors;
+
+        for (name, n) in &tally.kills {
+            *self.kills.entry(name.clone()).or_default() += n;
+        }
+    }
+
+    /// How many games ended in death
+    fn deaths(&self) -> u32 {
+        self.died.values().sum()
+    }
+}
+
+impl G {
+    /// Keep only the characters that are safe in a profile's file name
+    pub fn clean_profile_name(s: &str) -> String {
+        s.chars()
+            .filter(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+            .collect()
+    }
+
+    /// Where the profile's career is kept
+    pub fn career_path(&self) -> Option<PathBuf> {
+        let profile = self.options.profile.as_deref().unwrap_or(DEFAULT_PROFILE);
+
+        self.data_dir().map(|d| d.join("profiles").join(profile))
+    }
+
+    /// The stat that ran out, if the player died of it
+    pub fn death_cause(&self) -> Option<&'static str> {
+        [
+            (Stat::Strength, "strength"),
+            (Stat::Intelligence, "intelligence"),
+            (Stat::Dexterity, "dexterity"),
+        ]
+        .into_iter()
+        .find(|(s, _)| self.game.player_stat(*s) == 0)
+        .map(|(_, name)| name)
+    }
+
+    /// Add the game that just ended to the profile's career
+    ///
+    /// Games that aren't scored don't count here either.
+    pub fn record_career(&self, ending: Ending) {
+        if !self.game_counts() {
+            return;
+        }
+
+        let Some(path) = self.career_path() else {
+            return;
+        };
+
+        let cause = if ending == Ending::Died {
+            self.death_cause()
+        } else {
+            None
+        };
+
+        let result = (|| {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+
+            let mut f = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(false)
+                .open(&path)?;
+
+            f.lock()?;
+
+            let mut text = String::new();
+            f.read_to_string(&mut text)?;
+
+            let mut career = Career::parse(&text);
+            career.add(ending, cause, *self.game.turn(), &self.tally);
+
+            f.seek(SeekFrom::Start(0))?;
+            f.set_len(0)?;
+            f.write_all(career.to_text().as_bytes())?;
+
+            Ok::<_, io::Error>(())
+        })();
+
+        if let Err(err) = result {
+            self.popup_error(&format!(
+                "Couldn't save your career in {}: {}",
+                path.display(),
+                err
+            ));
+        }
+    }
+
+    /// Read the profile's career
+    pub fn load_career(&self) -> Career {
+        let Some(path) = self.career_path() else {
+            return Career::default();
+        };
+
+        let Ok(mut f) = File::open(path) else {
+            return Career::default();
+        };
+
+        let mut text = String::new();
+
+        if f.lock_shared().is_err() || f.read_to_string(&mut text).is_err() {
+            return Career::default();
+        }
+
+        Career::parse(&text)
+    }
+
+    /// Show the profile's career
+    pub fn career_screen(&self) {
+        self.show_career(&self.load_career());
+    }
+
+    /// Show a career
+    pub fn show_career(&self, c: &Career) {
+        let per_game = |n: u64| {
+            if c.games == 0 { 0 } else { n / c.games as u64 }
+        };
+
+        let mut left = vec![
+            format!("{:<18}{:>8}", "Games played", c.games),
+            format!("{:<18}{:>8}", "Won", c.won),
+            format!("{:<18}{:>8}", "Escaped", c.escaped),
+            format!("{:<18}{:>8}", "Quit", c.quit),
+            format!("{:<18}{:>8}", "Died", c.deaths()),
+        ];
+
+        for (cause, n) in &c.died {
+            left.push(format!("  {:<16}{:>8}", format!("of {}", cause), n));
+        }
+
+        left.extend([
+            String::new(),
+            format!("{:<18}{:>8}", "Average turns", per_game(c.turns)),
+            format!("{:<18}{:>8}", "Treasures found", c.treasures),
+            format!("{:<18}{:>8}", "Gold earned", c.gold),
+            format!("{:<18}{:>8}", "Vendors killed", c.vendors),
+        ]);
+
+        let mut kills: Vec<(&String, &u32)> = c.kills.iter().collect();
+        kills.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+
+        let mut right = vec![String::from("Monsters killed")];
+
+        if kills.is_empty() {
+            right.push(String::from("  None yet"));
+        }
+
+        right.extend(
+            kills
+                .iter()
+                .take(left.len() - 1)
+                .map(|(name, n)| format!("  {:<16}{:>6}", G::initial_upper(name), n)),
+        );
+
+        let rows = left.len().max(right.len()) as i32;
+
+        let w = self.popup(rows + 8, 64);
+
+        self.wcon(w, G::A_TITLE());
+        let title = match &self.options.profile {
+            Some(p) => format!("The career of {}", p),
+            None => String::from("Your career"),
+        };
+
+        self.mvwprintw_center(w, 2, &title);
+        self.wcoff(w, G::A_TITLE());
+
+        for (i, s) in left.iter().enumerate() {
+            self.ui.mvaddstr(w, 4 + i as i32, 4, s);
+        }
+
+        for (i, s) in right.iter().enumerate() {
+            self.ui.mvaddstr(w, 4 + i as i32, 36, s);
+        }
+
+        self.ui.attr_on(w, A_REVERSE);
+        self.mvwprintw_center_notrim(w, rows + 5, " Press any key ");
+        self.ui.attr_off(w, A_REVERSE);
+
+        self.ui.border(w);
+        self.ui.refresh(w);
+
+        self.getkey();
+
+        self.popup_close(w);
+
+        self.redraw_underwins();
+    }
+}
@@ -1,5 +1,5 @@
 use crate::G;
-use ncurses::*;
+use crate::frontend::*;
 
 use wizardscastle::armor::{Armor, ArmorType};
 use wizardscastle::player::{Gender, Race, Stat};
@@ -8,13 +8,13 @@ use wizardscastle::weapon::{Weapon, WeaponType};
 impl G {
     /// Do the intro
     pub fn intro(&self) {
-        let w = G::popup(15, 64);
+        let w = self.popup(15, 64);
 
         self.wcon(w, G::A_TITLE());
         self.mvwprintw_center(w, 2, "* * * THE WIZARD'S CASTLE * * *\n\n");
         self.wcoff(w, G::A_TITLE());
 
-        wattron(w, A_BOLD);
+        self.ui.attr_on(w, A_BOLD);
         self.wprintw_center(w, "Many cycles ago, in the Kingdom of N'DIC, the gnomic\n");
         self.wprintw_center(w, "wizard ZOT forged his great *ORB OF POWER*. He soon\n");
         self.wprintw_center(w, "vanished, leaving behind his vast subterranean castle\n");
@@ -34,39 +34,39 @@ impl G {
             w,
             "of now, *NONE* has ever emerged victoriously! BEWARE!!\n\n",
         );
-        wattroff(w, A_BOLD);
+        self.ui.attr_off(w, A_BOLD);
 
-        wattron(w, A_REVERSE);
+        self.ui.attr_on(w, A_REVERSE);
         self.wprintw_center(w, " Press any key ");
-        wattroff(w, A_REVERSE);
+        self.ui.attr_off(w, A_REVERSE);
 
-        box_(w, 0, 0);
+        self.ui.border(w);
 
-        wrefresh(w);
+        self.ui.refresh(w);
 
-        getch();
+        self.getkey();
 
-        G::popup_close(w);
+        self.popup_close(w);
     }
 
     /// Choose class
     pub fn choose_class(&mut self) {
-        let w = G::popup(7, 48);
+        let w = self.popup(7, 48);
 
         self.wcon(w, G::A_TITLE());
         self.mvwprintw_center(w, 2, "All right, Bold One. You may be an:");
         self.wcoff(w, G::A_TITLE());
 
-        wattr_on(w, A_BOLD);
+        self.ui.attr_on(w, A_BOLD);
         self.mvwprintw_center(w, 4, "|[E]|lf  |[D]|warf  Hu|[m]|an  |[H]|obbit");
-        wattr_off(w, A_BOLD);
+        self.ui.attr_off(w, A_BOLD);
 
-        box_(w, 0, 0);
+        self.ui.border(w);
 
-        wrefresh(w);
+        self.ui.refresh(w);
 
         loop {
-            let key = getch();
+            let key = self.getkey();
 
             match G::norm_key(key) {
                 'E' => break self.game.player_init(Race::Elf),
@@ -77,27 +77,27 @@ impl G {
             }
         }
 
-        G::popup_close(w);
+        self.popup_close(w);
     }
 
     /// Choose gender
     pub fn choose_gender(&mut self) {
-        let w = G::popup(7, 36);
+        let w = self.popup(7, 36);
 
         self.wcon(w, G::A_TITLE());
         self.mvwprintw_center(w, 2, "Which sex do you prefer?");
         self.wcoff(w, G::A_TITLE());
 
-        wattr_on(w, A_BOLD);
+        self.ui.attr_on(w, A_BOLD);
         self.mvwprintw_center(w, 4, "|[F]|emale  |[M]|ale");
-        wattr_off(w, A_BOLD);
+        self.ui.attr_off(w, A_BOLD);
 
-        box_(w, 0, 0);
+        self.ui.border(w);
 
-        wrefresh(w);
+        self.ui.refresh(w);
 
         loop {
-            let key = getch();
+            let key = self.getkey();
 
             match G::norm_key(key) {
                 'F' => break self.game.player_set_gender(Gender::Female),
@@ -106,12 +106,12 @@ impl G {
             }
         }
 
-        G::popup_close(w);
+        self.popup_close(w);
     }
 
     /// Choose stats
     pub fn choose_stats(&mut self) {
-        let w = G::popup(15, 50);
+        let w = self.popup(15, 50);
 
         let stats = [Stat::Strength, Stat::Intelligence, Stat::Dexterity];
 
@@ -122,7 +122,7 @@ impl G {
                 break;
             }
 
-            werase(w);
+            self.ui.erase(w);
 
             self.wcon(w, G::A_TITLE());
             self.mvwprintw_center(
@@ -172,7 +172,7 @@ impl G {
                 ),
             );
 
-            wattr_on(w, A_BOLD);
+            self.ui.attr_on(w, A_BOLD);
             self.mvwprintw_center(
                 w,
                 10,
@@ -183,14 +183,14 @@ impl G {
             );
 
             self.mvwprintw_center(w, 12, &format!("Press |[0]| to |[{}]|", additional_points));
-            wattr_off(w, A_BOLD);
+            self.ui.attr_off(w, A_BOLD);
 
-            box_(w, 0, 0);
+            self.ui.border(w);
 
-            wrefresh(w);
+            self.ui.refresh(w);
 
             loop {
-                let key = getch();
+                let key = self.getkey();
 
                 let nkey = G::norm_key(key);
 
@@ -210,7 +210,7 @@ impl G {
             }
         }
 
-        G::popup_close(w);
+        self.popup_close(w);
     }
 
     /// Return the number of types of armor the player can afford.
@@ -289,7 +289,7 @@ impl G {
             return;
         }
 
-        let w = G::popup(8 + armor_type_count as i32, 46);
+        let w = self.popup(8 + armor_type_count as i32, 46);
 
         self.wcon(w, G::A_TITLE());
         self.mvwprintw_center(
@@ -305,7 +305,7 @@ impl G {
 
         self.mvwprintw_center(w, 4, "Here is a list of armor you can buy.");
 
-        wattr_on(w, A_BOLD);
+        self.ui.attr_on(w, A_BOLD);
 
         let mut row_count = 0;
 
@@ -331,15 +331,15 @@ impl G {
             }
         }
 
-        wattr_off(w, A_BOLD);
+        self.ui.attr_off(w, A_BOLD);
 
-        box_(w, 0, 0);
+        self.ui.border(w);
 
-        wrefresh(w);
+        self.ui.refresh(w);
 
         loop {
             let r = loop {
-                let key = getch();
+                let key = self.getkey();
 
                 match G::norm_key(key) {
                     'P' => break self.game.player_purchase_armor(ArmorType::Plate, false),
@@ -355,7 +355,7 @@ impl G {
             }
         }
 
-        G::popup_close(w);
+        self.popup_close(w);
     }
 
     /// Buy weapon
@@ -373,7 +373,7 @@ impl G {
             return;
         }
 
-        let w = G::popup(8 + weapon_type_count as i32, 56);
+        let w = self.popup(8 + weapon_type_count as i32, 56);
 
         self.wcon(w, G::A_TITLE());
         self.mvwprintw_center(
@@ -389,7 +389,7 @@ impl G {
 
         self.mvwprintw_center(w, 4, "Here is a list of weapons you can buy.");
 
-        wattr_on(w, A_BOLD);
+        self.ui.attr_on(w, A_BOLD);
 
         let mut row_count = 0;
 
@@ -415,15 +415,15 @@ impl G {
             }
         }
 
-        wattr_off(w, A_BOLD);
+        self.ui.attr_off(w, A_BOLD);
 
-        box_(w, 0, 0);
+        self.ui.border(w);
 
-        wrefresh(w);
+        self.ui.refresh(w);
 
         loop {
             let r = loop {
-                let key = getch();
+                let key = self.getkey();
 
                 match G::norm_key(key) {
                     'S' => break self.game.player_purchase_weapon(WeaponType::Sword, false),
@@ -439,7 +439,7 @@ impl G {
             }
         }
 
-        G::popup_close(w);
+        self.popup_close(w);
     }
 
     /// Buy lamp
@@ -448,22 +448,22 @@ impl G {
             return;
         }
 
-        let w = G::popup(7, 40);
+        let w = self.popup(7, 40);
 
         self.wcon(w, G::A_TITLE());
         self.mvwprintw_center(w, 2, "Want to buy a lamp for 20 GPs?");
         self.wcoff(w, G::A_TITLE());
 
-        wattr_on(w, A_BOLD);
+        self.ui.attr_on(w, A_BOLD);
         self.mvwprintw_center(w, 4, "|[Y]|es  |[N]|o");
-        wattr_off(w, A_BOLD);
+        self.ui.attr_off(w, A_BOLD);
 
-        box_(w, 0, 0);
+        self.ui.border(w);
 
-        wrefresh(w);
+        self.ui.refresh(w);
 
         let r = loop {
-            let key = getch();
+            let key = self.getkey();
 
             match G::norm_key(key) {
                 'Y' => break self.game.player_purchase_lamp(true),
@@ -476,7 +476,7 @@ impl G {
             panic!("{:?}", err);
         }
 
-        G::popup_close(w);
+        self.popup_close(w);
     }
 
     /// Buy flares
@@ -487,9 +487,7 @@ impl G {
             return;
         }
 
-        let w = G::popup(9, 54);
-
-        let mut input = String::from("invalid");
+        let w = self.popup(9, 54);
 
         let mut success = false;
 
@@ -507,26 +505,20 @@ impl G {
 
         self.mvwprintw_center(w, 4, "Flares cost 1 GP each.");
 
-        box_(w, 0, 0);
+        self.ui.border(w);
 
         while !success {
-            let _ = mvwaddstr(w, 6, 15 + 22, "    "); // erase old input
-            let _ = mvwaddstr(w, 6, 15, "How many do you want? ");
-
-            wrefresh(w);
-            redrawwin(w);
+            self.ui.mvaddstr(w, 6, 15 + 22, "    "); // erase old input
+            self.ui.mvaddstr(w, 6, 15, "How many do you want? ");
 
-            nocbreak();
-            echo();
-            G::show_cursor(true);
+            self.ui.refresh(w);
+            self.ui.touch(w);
 
-            wgetnstr(w, &mut input, 2);
+            self.ui.show_cursor(true);
 
-            input = input.trim().to_string();
+            let input = self.read_line(w, 2).trim().to_string();
 
-            G::show_cursor(false);
-            noecho();
-            cbreak();
+            self.ui.show_cursor(false);
 
             if let Ok(num) = input.parse::<u32>() {
                 if num > gps {
@@ -542,6 +534,6 @@ impl G {
             }
         }
 
-        G::popup_close(w);
+        self.popup_close(w);
     }
 }
@@ -0,0 +1,213 @@
+//! The curses frontend
+use crate::frontend::{self, Attr, Frontend, Win};
+use ncurses::*;
+
+use std::cell::RefCell;
+
+/// Colors in the order of their pair numbers in `frontend`
+const PAIRS: [i16; 5] = [COLOR_YELLOW, COLOR_RED, COLOR_GREEN, COLOR_BLUE, COLOR_CYAN];
+
+pub struct Curses {
+    /// Windows by handle, with `None` where one was deleted
+    windows: RefCell<Vec<Option<WINDOW>>>,
+    colors: bool,
+}
+
+impl Curses {
+    /// Set up the terminal
+    pub fn new(use_color: bool) -> Curses {
+        initscr();
+
+        let colors = use_color && has_colors();
+
+        if colors {
+            start_color();
+
+            for (i, c) in PAIRS.iter().enumerate() {
+                init_pair(i as i16 + 1, *c, COLOR_BLACK);
+            }
+        }
+
+        keypad(stdscr(), true);
+
+        set_escdelay(100);
+
+        cbreak();
+        noecho();
+
+        refresh(); // If we don't do this first, windows don't show up
+
+        Curses {
+            windows: RefCell::new(Vec::new()),
+            colors,
+        }
+    }
+
+    fn win(&self, w: Win) -> WINDOW {
+        match self.windows.borrow().get(w.0) {
+            Some(Some(win)) => *win,
+            _ => panic!("no such window {}", w.0),
+        }
+    }
+
+    /// Keep a new window, in the slot of a deleted one if there is one
+    fn add(&self, win: WINDOW) -> Win {
+        let mut windows = self.windows.borrow_mut();
+
+        if let Some(i) = windows.iter().position(Option::is_none) {
+            windows[i] = Some(win);
+            return Win(i);
+        }
+
+        windows.push(Some(win));
+
+        Win(windows.len() - 1)
+    }
+
+    /// Translate our attributes into curses ones
+    fn attr(&self, a: Attr) -> attr_t {
+        let mut attr = 0;
+
+        if a & frontend::A_BOLD != 0 {
+            attr |= A_BOLD;
+        }
+
+        if a & frontend::A_DIM != 0 {
+            attr |= A_DIM;
+        }
+
+        if a & frontend::A_REVERSE != 0 {
+            attr |= A_REVERSE;
+        }
+
+        let pair = (a & frontend::COLOR_MASK) >> 8;
+
+        if self.colors && pair != 0 {
+            attr |= COLOR_PAIR(pair as i16);
+        }
+
+        attr
+    }
+}
+
+impl Drop for Curses {
+    fn drop(&mut self) {
+        nocbreak();
+        echo();
+        curs_set(CURSOR_VISIBILITY::CURSOR_VISIBLE);
+        endwin();
+    }
+}
+
+impl Frontend for Curses {
+    fn has_colors(&self) -> bool {
+        self.colors
+    }
+
+    fn lines(&self) -> i32 {
+        LINES()
+    }
+
+    fn cols(&self) -> i32 {
+        COLS()
+    }
+
+    fn newwin(&self, lines: i32, cols: i32, y: i32, x: i32) -> Win {
+        self.add(newwin(lines, cols, y, x))
+    }
+
+    fn derwin(&self, parent: Win, lines: i32, cols: i32, y: i32, x: i32) -> Win {
+        self.add(derwin(self.win(parent), lines, cols, y, x))
+    }
+
+    fn delwin(&self, w: Win) {
+        let win = self.win(w);
+
+        self.windows.borrow_mut()[w.0] = None;
+
+        delwin(win);
+    }
+
+    fn mvwin(&self, w: Win, y: i32, x: i32) {
+        mvwin(self.win(w), y, x);
+    }
+
+    fn resize(&self, w: Win, lines: i32, cols: i32) {
+        wresize(self.win(w), lines, cols);
+    }
+
+    fn scrollok(&self, w: Win, scroll: bool) {
+        scrollok(self.win(w), scroll);
+    }
+
+    fn erase(&self, w: Win) {
+        werase(self.win(w));
+    }
+
+    fn mv(&self, w: Win, y: i32, x: i32) {
+        wmove(self.win(w), y, x);
+    }
+
+    fn cury(&self, w: Win) -> i32 {
+        getcury(self.win(w))
+    }
+
+    fn curx(&self, w: Win) -> i32 {
+        getcurx(self.win(w))
+    }
+
+    fn maxx(&self, w: Win) -> i32 {
+        getmaxx(self.win(w))
+    }
+
+    fn attr_on(&self, w: Win, a: Attr) {
+        wattr_on(self.win(w), self.attr(a));
+    }
+
+    fn attr_off(&self, w: Win, a: Attr) {
+        wattr_off(self.win(w), self.attr(a));
+    }
+
+    fn attr_clear(&self, w: Win) {
+        wattr_set(self.win(w), 0, 0);
+    }
+
+    fn addstr(&self, w: Win, s: &str) {
+        let _ = waddstr(self.win(w), s);
+    }
+
+    fn border(&self, w: Win) {
+        box_(self.win(w), 0, 0);
+    }
+
+    fn refresh(&self, w: Win) {
+        wrefresh(self.win(w));
+    }
+
+    fn touch(&self, w: Win) {
+        redrawwin(self.win(w));
+    }
+
+    fn clear(&self) {
+        clear();
+        refresh();
+    }
+
+    fn show_cursor(&self, show: bool) {
+        if show {
+            curs_set(CURSOR_VISIBILITY::CURSOR_VISIBLE);
+        } else {
+            curs_set(CURSOR_VISIBILITY::CURSOR_INVISIBLE);
+        }
+    }
+
+    fn getch(&self, timeout: i32) -> Option<i32> {
+        ncurses::timeout(timeout);
+
+        let key = getch();
+
+        ncurses::timeout(-1);
+
+        if key == ERR { None } else { Some(key) }
+    }
+}
@@ -0,0 +1,93 @@
+//! Exploring: heading for the nearest room on the level the player hasn't
+//! seen, again and again, until something turns up
+use crate::G;
+use crate::stat::StatMode;
+use crate::travel::{Loc, PathMap, Step, Travel, Trip};
+
+use wizardscastle::game::Event;
+use wizardscastle::room::RoomType;
+
+/// Tell if a room has nothing in it to stop for
+pub fn is_dull(r: &RoomType) -> bool {
+    matches!(r, RoomType::Empty | RoomType::Entrance)
+}
+
+impl PathMap<'_> {
+    /// The first step from `start` toward the nearest room on its level that
+    /// hasn't been `seen`, going only through rooms with nothing in them
+    pub fn explore_step(&self, start: Loc, seen: impl Fn(Loc) -> bool) -> Option<Step> {
+        self.find_path(
+            start,
+            |loc| loc.2 == start.2 && !seen(loc),
+            |loc| is_dull(&(self.room)(loc)),
+        )
+        .and_then(|(_, path)| path.front().copied())
+    }
+}
+
+impl G {
+    /// The first step toward the nearest room on this level the player
+    /// hasn't seen
+    pub fn explore_step(&self) -> Option<Step> {
+        self.path_map()
+            .explore_step(self.player_loc(), |(x, y, z)| {
+                self.game.dungeon_room_at(x, y, z).discovered
+            })
+    }
+
+    /// Why exploring should stop in the room the player just came to, if
+    /// it should
+    pub fn explore_stop_reason(&self, event: &Event) -> Option<String> {
+        match event {
+            Event::Combat(m) => {
+                let mon_str = G::monster_name(*m);
+                Some(format!(
+                    "there's {} {} here",
+                    G::get_article(&mon_str),
+                    mon_str
+                ))
+            }
+            Event::Vendor => Some(String::from("there's a vendor here")),
+            Event::Sinkhole => Some(String::from("you fell into a sinkhole")),
+            Event::Warp => Some(String::from("you went through a warp")),
+            Event::Treasure(_) => Some(String::from("you found a treasure")),
+            _ => match self.statmode {
+                StatMode::Pool
+                | StatMode::Book
+                | StatMode::Chest
+                | StatMode::CrystalOrb
+                | StatMode::StairsUp
+                | StatMode::StairsDown => Some(format!(
+                    "there's {} here",
+                    G::room_name(self.game.room_at_player().room_type())
+                )),
+                _ => None,
+            },
+        }
+    }
+
+    /// Explore the level until something turns up
+    pub fn explore(&mut self) {
+        if self.game.player_is_blind() {
+            self.update_log_error(&format!(
+                "** You can't explore blind, dumb {}",
+                self.race_name()
+            ));
+            return;
+        }
+
+        if self.explore_step().is_none() {
+            self.update_log_error("** There's nowhere left to explore that you can reach.");
+            return;
+        }
+
+        self.update_log("You set off exploring.");
+
+        self.travel = Some(Travel::new(Trip::Explore, self.messages.borrow().len()));
+
+        // The first step is this turn
+        if let Some(step) = self.next_travel_step() {
+            self.take_step(step);
+        }
+    }
+}
@@ -0,0 +1,117 @@
+//! Keeping score in a fight
+//!
+//! The backend doesn't say how much life a monster has left, so it's
+//! estimated the way the original game worked it out: a monster has two
+//! more hit points than its rank, where the kobold is first and the vendor
+//! last.
+use crate::G;
+use crate::bestiary::MONSTERS;
+
+use wizardscastle::monster::MonsterType;
+
+/// How a fight is going
+#[derive(Debug, Clone)]
+pub struct Fight {
+    name: String,
+
+    /// Hit points the monster started with, as best we can tell
+    hp: u32,
+
+    dealt: u32,
+    hits: u32,
+    misses: u32,
+    taken: u32,
+
+    /// How much strength the player has lost
+    lost: u32,
+
+    /// What happened last, for the status window
+    last: Option<String>,
+}
+
+impl Fight {
+    pub fn new(monster: MonsterType) -> Fight {
+        let rank = MONSTERS
+            .iter()
+            .position(|m| *m == monster)
+            .map_or(MONSTERS.len(), |i| i + 1) as u32;
+
+        Fight {
+            name: G::monster_name(monster),
+            hp: rank + 2,
+            dealt: 0,
+            hits: 0,
+            misses: 0,
+            taken: 0,
+            lost: 0,
+            last: None,
+        }
+    }
+}
+
+impl G {
+    /// Count a hit on the monster
+    pub fn fight_hit(&mut self, damage: u32) {
+        if let Some(f) = &mut self.fight {
+            f.dealt += damage;
+            f.hits += 1;
+            f.last = Some(format!("You hit for {}", damage));
+        }
+    }
+
+    /// Count a miss
+    pub fn fight_miss(&mut self) {
+        if let Some(f) = &mut self.fight {
+            f.misses += 1;
+            f.last = Some(String::from("You missed"));
+        }
+    }
+
+    /// Count a hit on the player, and the strength it cost
+    pub fn fight_hurt(&mut self, damage: u32, lost: u32) {
+        if let Some(f) = &mut self.fight {
+            f.taken += damage;
+            f.lost += lost;
+            f.last = Some(format!("It hit for {}, -{} ST", damage, lost));
+        }
+    }
+
+    /// Show how the fight is going in the status window
+    pub fn fight_status(&self) {
+        let Some(f) = &self.fight else {
+            return;
+        };
+
+        let left = f.hp.saturating_sub(f.dealt);
+
+        self.mvwprintw_center(
+            self.statwin,
+            9,
+            &format!("{}: ~{} of {} HP", G::initial_upper(&f.name), left, f.hp),
+        );
+
+        if let Some(last) = &f.last {
+            self.mvwprintw_center(self.statwin, 15, last);
+        }
+    }
+
+    /// Log how the fight went, if anything happened, and forget it
+    pub fn end_fight(&mut self) {
+        let Some(f) = self.fight.take() else {
+            return;
+        };
+
+        // Nothing to tell if it was over before it started
+        if f.hits + f.misses == 0 && f.taken == 0 {
+            return;
+        }
+
+        let misses = if f.misses == 1 { "miss" } else { "misses" };
+        let hits = if f.hits == 1 { "hit" } else { "hits" };
+
+        self.update_log(&format!(
+            "That fight: you dealt {} in {} {} ({} {}) and took {}, {} of it from strength.",
+            f.dealt, f.hits, hits, f.misses, misses, f.taken, f.lost
+        ));
+    }
+}
@@ -0,0 +1,126 @@
+//! The frontend the game draws on and reads keys from
+//!
+//! This looks a lot like curses, since that's what it was written against,
+//! but `G` only ever talks to it through the `Frontend` trait. `Curses` (in
+//! `curses.rs`) is the real terminal; `Headless` (in `headless.rs`) keeps the
+//! screen in memory for tests and other frontends.
+
+/// Handle for a window
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Win(pub usize);
+
+/// Output attributes: the low bits are flags, the next byte is a color
+pub type Attr = u32;
+
+pub const A_NORMAL: Attr = 0;
+pub const A_BOLD: Attr = 1;
+pub const A_DIM: Attr = 1 << 1;
+pub const A_REVERSE: Attr = 1 << 2;
+
+pub const YELLOW: Attr = 1 << 8;
+pub const RED: Attr = 2 << 8;
+pub const GREEN: Attr = 3 << 8;
+pub const BLUE: Attr = 4 << 8;
+pub const CYAN: Attr = 5 << 8;
+
+/// Mask for the color part of an `Attr`
+pub const COLOR_MASK: Attr = 0xff << 8;
+
+// Special keys. These have the same values as in curses.
+pub const KEY_DOWN: i32 = 0o402;
+pub const KEY_UP: i32 = 0o403;
+pub const KEY_LEFT: i32 = 0o404;
+pub const KEY_RIGHT: i32 = 0o405;
+pub const KEY_HOME: i32 = 0o406;
+pub const KEY_BACKSPACE: i32 = 0o407;
+pub const KEY_NPAGE: i32 = 0o522;
+pub const KEY_PPAGE: i32 = 0o523;
+pub const KEY_ENTER: i32 = 0o527;
+pub const KEY_END: i32 = 0o550;
+pub const KEY_RESIZE: i32 = 0o632;
+
+pub trait Frontend {
+    /// Tell if colors can be used
+    fn has_colors(&self) -> bool;
+
+    /// Screen height
+    fn lines(&self) -> i32;
+
+    /// Screen width
+    fn cols(&self) -> i32;
+
+    /// Make a new window
+    fn newwin(&self, lines: i32, cols: i32, y: i32, x: i32) -> Win;
+
+    /// Make a window inside another one, sharing its contents
+    fn derwin(&self, parent: Win, lines: i32, cols: i32, y: i32, x: i32) -> Win;
+
+    /// Get rid of a window
+    fn delwin(&self, w: Win);
+
+    /// Move a window on the screen
+    fn mvwin(&self, w: Win, y: i32, x: i32);
+
+    /// Change the size of a window, keeping what fits of its contents
+    fn resize(&self, w: Win, lines: i32, cols: i32);
+
+    /// Let a window scroll when text runs off the bottom
+    fn scrollok(&self, w: Win, scroll: bool);
+
+    /// Blank a window
+    fn erase(&self, w: Win);
+
+    /// Move the cursor in a window
+    fn mv(&self, w: Win, y: i32, x: i32);
+
+    /// Cursor row in a window
+    fn cury(&self, w: Win) -> i32;
+
+    /// Cursor column in a window
+    fn curx(&self, w: Win) -> i32;
+
+    /// Window width
+    fn maxx(&self, w: Win) -> i32;
+
+    /// Turn on attributes for later output
+    fn attr_on(&self, w: Win, a: Attr);
+
+    /// Turn off attributes for later output
+    fn attr_off(&self, w: Win, a: Attr);
+
+    /// Turn off all attributes
+    fn attr_clear(&self, w: Win);
+
+    /// Write a string at the cursor
+    fn addstr(&self, w: Win, s: &str);
+
+    /// Draw a border around a window in the current attributes
+    fn border(&self, w: Win);
+
+    /// Show a window's changes on the screen
+    fn refresh(&self, w: Win);
+
+    /// Make the next refresh redraw the entire window
+    fn touch(&self, w: Win);
+
+    /// Blank the whole screen
+    fn clear(&self);
+
+    /// Show or hide the cursor
+    fn show_cursor(&self, show: bool);
+
+    /// Wait for a key, giving up after `timeout` ms. A negative timeout
+    /// waits forever.
+    fn getch(&self, timeout: i32) -> Option<i32>;
+
+    /// Move the cursor and write a string
+    fn mvaddstr(&self, w: Win, y: i32, x: i32, s: &str) {
+        self.mv(w, y, x);
+        self.addstr(w, s);
+    }
+
+    /// Write a character at the cursor
+    fn addch(&self, w: Win, c: char) {
+        self.addstr(w, c.encode_utf8(&mut [0; 4]));
+    }
+}
@@ -1,9 +1,9 @@
 use crate::G;
-use ncurses::*;
+use crate::frontend::*;
+use crate::scores::Score;
 
 use wizardscastle::armor::ArmorType;
 use wizardscastle::game::GameState;
-use wizardscastle::player::Stat;
 use wizardscastle::weapon::WeaponType;
 
 use std::cmp;
@@ -12,7 +12,7 @@ use std::fmt::Write;
 impl G {
     /// Initial screen if dead
     fn dead1(&self) {
-        let w = G::popup(9, 52);
+        let w = self.popup(9, 52);
 
         self.wcon(w, G::A_TITLE());
         self.mvwprintw_center(
@@ -27,31 +27,28 @@ impl G {
 
         let mut cod = String::from("You died due to lack of ");
 
-        if self.game.player_stat(Stat::Strength) == 0 {
-            cod.push_str("strength.");
-        } else if self.game.player_stat(Stat::Intelligence) == 0 {
-            cod.push_str("intelligence.");
-        } else if self.game.player_stat(Stat::Dexterity) == 0 {
-            cod.push_str("dexterity.");
+        if let Some(cause) = self.death_cause() {
+            cod.push_str(cause);
+            cod.push('.');
         }
 
         self.wprintw_center(w, &cod);
 
-        let _ = waddstr(w, "\n\n");
+        self.ui.addstr(w, "\n\n");
 
         self.wprintw_center(w, "|[C]|ontinue");
 
-        box_(w, 0, 0);
+        self.ui.border(w);
 
-        wrefresh(w);
+        self.ui.refresh(w);
 
         loop {
-            if G::norm_key(getch()) == 'C' {
+            if G::norm_key(self.getkey()) == 'C' {
                 break;
             }
         }
 
-        G::popup_close(w);
+        self.popup_close(w);
 
         self.redraw_underwins();
     }
@@ -60,7 +57,7 @@ impl G {
     fn exit1(&self, win: bool) {
         let width = if win { 50 } else { 53 };
 
-        let w = G::popup(9, width);
+        let w = self.popup(9, width);
 
         let title = if win {
             "A glorious victory!"
@@ -76,23 +73,23 @@ impl G {
         let punc = if win { "!" } else { "." };
         let copy = format!("You left the castle with{} the Orb of Zot{}\n\n", out, punc);
 
-        let _ = waddstr(w, "\n\n");
+        self.ui.addstr(w, "\n\n");
 
         self.wprintw_center(w, &copy);
 
         self.wprintw_center(w, "|[C]|ontinue");
 
-        box_(w, 0, 0);
+        self.ui.border(w);
 
-        wrefresh(w);
+        self.ui.refresh(w);
 
         loop {
-            if G::norm_key(getch()) == 'C' {
+            if G::norm_key(self.getkey()) == 'C' {
                 break;
             }
         }
 
-        G::popup_close(w);
+        self.popup_close(w);
 
         self.redraw_underwins();
     }
@@ -160,8 +157,14 @@ impl G {
             "When you left the castle, you had:"
         };
 
-        // width is max len of all weapon_str, title_str, and turn_str + 10
-        let width = cmp::max(weapon_str.len(), cmp::max(turn_str.len(), title_str.len())) + 10;
+        // The prompt without its markup
+        let prompt_len = "[P]lay again, [C]areer, or [Q]uit".len();
+
+        // width is max len of all weapon_str, title_str, turn_str, and the
+        // prompt + 10
+        let width = cmp::max(weapon_str.len(), cmp::max(turn_str.len(), title_str.len()))
+            .max(prompt_len)
+            + 10;
 
         let mut height = 10;
         let mut add_height = 0;
@@ -199,13 +202,13 @@ impl G {
 
         height += add_height;
 
-        let w = G::popup(height as i32, width as i32);
+        let w = self.popup(height as i32, width as i32);
 
         self.wcon(w, G::A_TITLE());
         self.mvwprintw_center(w, 2, title_str);
         self.wcoff(w, G::A_TITLE());
 
-        wmove(w, 4, 0);
+        self.ui.mv(w, 4, 0);
 
         if !dead {
             self.wprintw_center(w, "Your miserable life!\n");
@@ -239,25 +242,30 @@ impl G {
             self.wprintw_center(w, "Nothing!\n");
         }
 
-        let _ = waddstr(w, "\n");
+        self.ui.addstr(w, "\n");
 
         self.wprintw_center(w, &format!("{}\n\n", turn_str));
 
-        self.wprintw_center(w, "|[P]|lay again or |[Q]|uit");
+        self.wprintw_center(w, "|[P]|lay again, |[C]|areer, or |[Q]|uit");
 
-        box_(w, 0, 0);
+        self.ui.border(w);
 
-        wrefresh(w);
+        self.ui.refresh(w);
 
         let play_again = loop {
-            match G::norm_key(getch()) {
+            match G::norm_key(self.getkey()) {
                 'P' => break true,
                 'Q' => break false,
+                'C' => {
+                    self.career_screen();
+                    self.ui.touch(w);
+                    self.ui.refresh(w);
+                }
                 _ => (),
             }
         };
 
-        G::popup_close(w);
+        self.popup_close(w);
 
         self.redraw_underwins();
 
@@ -268,7 +276,7 @@ impl G {
     fn restart_screen(&self, play_again: bool) {
         let width = if play_again { 35 } else { 50 };
 
-        let w = G::popup(7, width);
+        let w = self.popup(7, width);
 
         self.wcon(w, G::A_TITLE());
 
@@ -284,7 +292,7 @@ impl G {
 
         self.wcoff(w, G::A_TITLE());
 
-        wattr_on(w, A_REVERSE);
+        self.ui.attr_on(w, A_REVERSE);
 
         if play_again {
             self.mvwprintw_center_notrim(w, 4, " Press any key to start ");
@@ -292,21 +300,22 @@ impl G {
             self.mvwprintw_center_notrim(w, 4, " Press any key to exit ");
         }
 
-        wattr_off(w, A_REVERSE);
+        self.ui.attr_off(w, A_REVERSE);
 
-        box_(w, 0, 0);
+        self.ui.border(w);
 
-        wrefresh(w);
+        self.ui.refresh(w);
 
-        getch();
+        self.getkey();
 
-        G::popup_close(w);
+        self.popup_close(w);
 
         self.redraw_underwins();
     }
 
-    /// Show gameover summary
-    pub fn game_summary(&self) -> bool {
+    /// Show gameover summary, with the high scores if the game made it
+    /// into them
+    pub fn game_summary(&self, scores: Option<(Vec<Score>, Option<usize>)>) -> bool {
         let dead;
 
         match self.game.state() {
@@ -332,6 +341,10 @@ impl G {
 
         let play_again = self.final_inventory(dead);
 
+        if let Some((scores, new)) = scores {
+            self.hall_of_fame(&scores, new);
+        }
+
         self.restart_screen(play_again);
 
         play_again
@@ -0,0 +1,482 @@
+//! A frontend that keeps the screen in memory
+//!
+//! Keys are queued up ahead of time with `push_keys()`, and what would have
+//! been on the terminal can be read back with `screen()` and `cell()`. Clones
+//! share the same screen, so keep one to look at after handing the other to
+//! `G`.
+use crate::frontend::{A_NORMAL, Attr, Frontend, KEY_RESIZE, Win};
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+/// A character on the screen and its attributes
+pub type Cell = (char, Attr);
+
+const BLANK: Cell = (' ', A_NORMAL);
+
+/// A pretend key that saves a copy of the screen instead of being read
+pub const SNAPSHOT: i32 = -2;
+
+struct Window {
+    /// Position on the screen, or in the parent for derived windows
+    y: i32,
+    x: i32,
+
+    lines: i32,
+    cols: i32,
+
+    /// For derived windows, the window holding the contents
+    parent: Option<Win>,
+
+    cury: i32,
+    curx: i32,
+    attr: Attr,
+    scroll: bool,
+
+    cells: Vec<Vec<Cell>>,
+}
+
+struct State {
+    lines: i32,
+    cols: i32,
+    windows: Vec<Option<Window>>,
+    screen: Vec<Vec<Cell>>,
+    keys: VecDeque<i32>,
+    resizes: VecDeque<(i32, i32)>,
+    snapshots: Vec<Vec<String>>,
+}
+
+#[derive(Clone)]
+pub struct Headless {
+    colors: bool,
+    state: Rc<RefCell<State>>,
+}
+
+impl Headless {
+    /// Make a blank screen of the given size
+    pub fn new(lines: i32, cols: i32, colors: bool) -> Headless {
+        Headless {
+            colors,
+            state: Rc::new(RefCell::new(State {
+                lines,
+                cols,
+                windows: Vec::new(),
+                screen: vec![vec![BLANK; cols as usize]; lines as usize],
+                keys: VecDeque::new(),
+                resizes: VecDeque::new(),
+                snapshots: Vec::new(),
+            })),
+        }
+    }
+
+    /// Queue up keys to be read
+    pub fn push_keys(&self, keys: &[i32]) {
+        self.state.borrow_mut().keys.extend(keys);
+    }
+
+    /// Queue up a string of keys
+    pub fn push_str(&self, s: &str) {
+        self.state
+            .borrow_mut()
+            .keys
+            .extend(s.chars().map(|c| c as i32));
+    }
+
+    /// Queue up a `KEY_RESIZE`. The screen changes size when it's read.
+    pub fn resize_screen(&self, lines: i32, cols: i32) {
+        let mut state = self.state.borrow_mut();
+
+        state.keys.push_back(KEY_RESIZE);
+        state.resizes.push_back((lines, cols));
+    }
+
+    /// The screen as text, one string per line
+    pub fn screen(&self) -> Vec<String> {
+        self.state.borrow().text()
+    }
+
+    /// The screens saved by `SNAPSHOT` keys, in order
+    pub fn snapshots(&self) -> Vec<Vec<String>> {
+        self.state.borrow().snapshots.clone()
+    }
+
+    /// A character on the screen
+    pub fn cell(&self, y: i32, x: i32) -> Cell {
+        self.state.borrow().screen[y as usize][x as usize]
+    }
+}
+
+impl State {
+    fn text(&self) -> Vec<String> {
+        self.screen
+            .iter()
+            .map(|row| row.iter().map(|c| c.0).collect())
+            .collect()
+    }
+
+    fn win(&self, w: Win) -> &Window {
+        match self.windows.get(w.0) {
+            Some(Some(win)) => win,
+            _ => panic!("no such window {}", w.0),
+        }
+    }
+
+    fn win_mut(&mut self, w: Win) -> &mut Window {
+        match self.windows.get_mut(w.0) {
+            Some(Some(win)) => win,
+            _ => panic!("no such window {}", w.0),
+        }
+    }
+
+    /// Find the window holding the contents of `w`, and where `w` is in it
+    fn root(&self, w: Win) -> (Win, i32, i32) {
+        let win = self.win(w);
+
+        match win.parent {
+            Some(p) => {
+                let (r, ry, rx) = self.root(p);
+                (r, ry + win.y, rx + win.x)
+            }
+            None => (w, 0, 0),
+        }
+    }
+
+    /// Put a cell in a window, ignoring anything outside it
+    fn put(&mut self, w: Win, y: i32, x: i32, c: Cell) {
+        let (lines, cols) = {
+            let win = self.win(w);
+            (win.lines, win.cols)
+        };
+
+        if y < 0 || x < 0 || y >= lines || x >= cols {
+            return;
+        }
+
+        let (r, ry, rx) = self.root(w);
+
+        if let Some(cell) = self
+            .win_mut(r)
+            .cells
+            .get_mut((ry + y) as usize)
+            .and_then(|row| row.get_mut((rx + x) as usize))
+        {
+            *cell = c;
+        }
+    }
+
+    /// Get a cell from a window that holds its own contents
+    fn get(&self, w: Win, y: i32, x: i32) -> Option<Cell> {
+        self.win(w)
+            .cells
+            .get(y as usize)
+            .and_then(|row| row.get(x as usize))
+            .copied()
+    }
+
+    /// Scroll a window's contents up a line
+    fn scroll_up(&mut self, w: Win) {
+        let (lines, cols) = {
+            let win = self.win(w);
+            (win.lines, win.cols)
+        };
+
+        let (r, ry, rx) = self.root(w);
+
+        for y in 0..lines {
+            for x in 0..cols {
+                let c = if y + 1 < lines {
+                    self.get(r, ry + y + 1, rx + x).unwrap_or(BLANK)
+                } else {
+                    BLANK
+                };
+
+                self.put(w, y, x, c);
+            }
+        }
+    }
+
+    /// Move the cursor to the next line, scrolling if allowed
+    ///
+    /// Returns false if the cursor is stuck at the bottom.
+    fn newline(&mut self, w: Win) -> bool {
+        let win = self.win(w);
+
+        if win.cury + 1 < win.lines {
+            let win = self.win_mut(w);
+            win.cury += 1;
+            win.curx = 0;
+            true
+        } else if win.scroll {
+            self.scroll_up(w);
+            self.win_mut(w).curx = 0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl Frontend for Headless {
+    fn has_colors(&self) -> bool {
+        self.colors
+    }
+
+    fn lines(&self) -> i32 {
+        self.state.borrow().lines
+    }
+
+    fn cols(&self) -> i32 {
+        self.state.borrow().cols
+    }
+
+    fn newwin(&self, lines: i32, cols: i32, y: i32, x: i32) -> Win {
+        let mut state = self.state.borrow_mut();
+
+        state.windows.push(Some(Window {
+            y,
+            x,
+            lines,
+            cols,
+            parent: None,
+            cury: 0,
+            curx: 0,
+            attr: A_NORMAL,
+            scroll: false,
+            cells: vec![vec![BLANK; cols as usize]; lines as usize],
+        }));
+
+        Win(state.windows.len() - 1)
+    }
+
+    fn derwin(&self, parent: Win, lines: i32, cols: i32, y: i32, x: i32) -> Win {
+        let mut state = self.state.borrow_mut();
+
+        state.windows.push(Some(Window {
+            y,
+            x,
+            lines,
+            cols,
+            parent: Some(parent),
+            cury: 0,
+            curx: 0,
+            attr: A_NORMAL,
+            scroll: false,
+            cells: Vec::new(),
+        }));
+
+        Win(state.windows.len() - 1)
+    }
+
+    fn delwin(&self, w: Win) {
+        self.state.borrow_mut().windows[w.0] = None;
+    }
+
+    fn mvwin(&self, w: Win, y: i32, x: i32) {
+        let mut state = self.state.borrow_mut();
+        let win = state.win_mut(w);
+
+        win.y = y;
+        win.x = x;
+    }
+
+    fn resize(&self, w: Win, lines: i32, cols: i32) {
+        let mut state = self.state.borrow_mut();
+        let win = state.win_mut(w);
+
+        win.lines = lines;
+        win.cols = cols;
+        win.cury = win.cury.min(lines - 1);
+        win.curx = win.curx.min(cols - 1);
+
+        if win.parent.is_none() {
+            win.cells.resize(lines as usize, Vec::new());
+
+            for row in win.cells.iter_mut() {
+                row.resize(cols as usize, BLANK);
+            }
+        }
+    }
+
+    fn scrollok(&self, w: Win, scroll: bool) {
+        self.state.borrow_mut().win_mut(w).scroll = scroll;
+    }
+
+    fn erase(&self, w: Win) {
+        let mut state = self.state.borrow_mut();
+
+        let (lines, cols) = {
+            let win = state.win(w);
+            (win.lines, win.cols)
+        };
+
+        for y in 0..lines {
+            for x in 0..cols {
+                state.put(w, y, x, BLANK);
+            }
+        }
+
+        let win = state.win_mut(w);
+        win.cury = 0;
+        win.curx = 0;
+    }
+
+    fn mv(&self, w: Win, y: i32, x: i32) {
+        let mut state = self.state.borrow_mut();
+        let win = state.win_mut(w);
+
+        if y >= 0 && x >= 0 && y < win.lines && x < win.cols {
+            win.cury = y;
+            win.curx = x;
+        }
+    }
+
+    fn cury(&self, w: Win) -> i32 {
+        self.state.borrow().win(w).cury
+    }
+
+    fn curx(&self, w: Win) -> i32 {
+        self.state.borrow().win(w).curx
+    }
+
+    fn maxx(&self, w: Win) -> i32 {
+        self.state.borrow().win(w).cols
+    }
+
+    fn attr_on(&self, w: Win, a: Attr) {
+        self.state.borrow_mut().win_mut(w).attr |= a;
+    }
+
+    fn attr_off(&self, w: Win, a: Attr) {
+        self.state.borrow_mut().win_mut(w).attr &= !a;
+    }
+
+    fn attr_clear(&self, w: Win) {
+        self.state.borrow_mut().win_mut(w).attr = A_NORMAL;
+    }
+
+    fn addstr(&self, w: Win, s: &str) {
+        let mut state = self.state.borrow_mut();
+
+        for c in s.chars() {
+            let (y, x, cols, attr) = {
+                let win = state.win(w);
+                (win.cury, win.curx, win.cols, win.attr)
+            };
+
+            if c == '\n' {
+                // Like curses, a newline clears the rest of the line
+                for cx in x..cols {
+                    state.put(w, y, cx, BLANK);
+                }
+
+                if !state.newline(w) {
+                    return;
+                }
+
+                continue;
+            }
+
+            state.put(w, y, x, (c, attr));
+
+            if x + 1 < cols {
+                state.win_mut(w).curx += 1;
+            } else if !state.newline(w) {
+                return;
+            }
+        }
+    }
+
+    fn border(&self, w: Win) {
+        let mut state = self.state.borrow_mut();
+
+        let (lines, cols, attr) = {
+            let win = state.win(w);
+            (win.lines, win.cols, win.attr)
+        };
+
+        for x in 0..cols {
+            state.put(w, 0, x, ('-', attr));
+            state.put(w, lines - 1, x, ('-', attr));
+        }
+
+        for y in 0..lines {
+            state.put(w, y, 0, ('|', attr));
+            state.put(w, y, cols - 1, ('|', attr));
+        }
+
+        for (y, x) in [(0, 0), (0, cols - 1), (lines - 1, 0), (lines - 1, cols - 1)] {
+            state.put(w, y, x, ('+', attr));
+        }
+    }
+
+    fn refresh(&self, w: Win) {
+        let mut state = self.state.borrow_mut();
+
+        let (lines, cols) = {
+            let win = state.win(w);
+            (win.lines, win.cols)
+        };
+
+        let (r, ry, rx) = state.root(w);
+
+        let (wy, wx) = {
+            let root = state.win(r);
+            (root.y + ry, root.x + rx)
+        };
+
+        for y in 0..lines {
+            for x in 0..cols {
+                let (sy, sx) = (wy + y, wx + x);
+
+                if sy < 0 || sx < 0 || sy >= state.lines || sx >= state.cols {
+                    continue;
+                }
+
+                if let Some(c) = state.get(r, ry + y, rx + x) {
+                    state.screen[sy as usize][sx as usize] = c;
+                }
+            }
+        }
+    }
+
+    fn touch(&self, _w: Win) {
+        // Every refresh copies the whole window anyway
+    }
+
+    fn clear(&self) {
+        let mut state = self.state.borrow_mut();
+
+        for row in state.screen.iter_mut() {
+            row.fill(BLANK);
+        }
+    }
+
+    fn show_cursor(&self, _show: bool) {
+        // There's no cursor to show
+    }
+
+    fn getch(&self, timeout: i32) -> Option<i32> {
+        let mut state = self.state.borrow_mut();
+
+        loop {
+            match state.keys.pop_front() {
+                Some(SNAPSHOT) => {
+                    let text = state.text();
+                    state.snapshots.push(text);
+                }
+                Some(KEY_RESIZE) => {
+                    if let Some((lines, cols)) = state.resizes.pop_front() {
+                        state.lines = lines;
+                        state.cols = cols;
+                        state.screen = vec![vec![BLANK; cols as usize]; lines as usize];
+                    }
+
+                    return Some(KEY_RESIZE);
+                }
+                None if timeout < 0 => panic!("headless frontend ran out of keys"),
+                key => return key,
+            }
+        }
+    }
+}
@@ -1,20 +1,89 @@
 use crate::G;
-use ncurses::*;
+use crate::keymap::{Action, KeyContext, Movement};
 
 impl G {
+    /// Build the rows of the help table from the active keymap
+    fn help_rows(&self) -> Vec<String> {
+        let columns = [
+            vec![
+                (Action::North, "North"),
+                (Action::South, "South"),
+                (Action::West, "West"),
+                (Action::East, "East"),
+                (Action::Up, "Up"),
+                (Action::Down, "Down"),
+                (Action::LevelUp, "Level up"),
+                (Action::LevelDown, "Level down"),
+                (Action::Look, "Look"),
+                (Action::Travel, "Travel"),
+                (Action::Run, "Run"),
+            ],
+            vec![
+                (Action::Trade, "Trade"),
+                (Action::Attack, "Attack"),
+                (Action::Lamp, "Lamp"),
+                (Action::Flare, "Flare"),
+                (Action::Inventory, "Inventory"),
+                (Action::Teleport, "Teleport"),
+                (Action::Copyright, "Copyright"),
+                (Action::Annotate, "Annotate"),
+                (Action::Rumors, "Rumors"),
+                (Action::Bestiary, "Bestiary"),
+            ],
+            vec![
+                (Action::Read, "Read"),
+                (Action::Down, "Drink"),
+                (Action::Open, "Open"),
+                (Action::Gaze, "Gaze"),
+                (Action::Help, "Help"),
+                (Action::Legend, "Legend"),
+                (Action::History, "Messages"),
+                (Action::Notes, "Journal"),
+                (Action::Explore, "Explore"),
+                (Action::Quit, "Quit"),
+            ],
+        ];
+
+        let labels: Vec<Vec<String>> = columns
+            .iter()
+            .map(|col| {
+                col.iter()
+                    .map(|(a, name)| self.keys.label(KeyContext::Explore, *a, name))
+                    .collect()
+            })
+            .collect();
+
+        let widths: Vec<usize> = labels
+            .iter()
+            .map(|col| col.iter().map(|s| s.len()).max().unwrap_or(0))
+            .collect();
+
+        let height = labels.iter().map(|col| col.len()).max().unwrap_or(0);
+
+        (0..height)
+            .map(|row| {
+                labels
+                    .iter()
+                    .enumerate()
+                    .map(|(i, col)| {
+                        let s = col.get(row).map(|s| s.as_str()).unwrap_or("");
+                        format!("{:<w$}", s, w = widths[i])
+                    })
+                    .collect::<Vec<_>>()
+                    .join("   ")
+            })
+            .collect()
+    }
+
     /// Show help
     pub fn help(&self) {
-        let w = G::popup(15, 44);
-
-        let strs = [
-            "[N]orth     [T]rade       [R]ead ",
-            "[S]outh     [A]ttack      [D]rink",
-            "[W]est      [L]amp        [O]pen ",
-            "[E]ast      [F]lare       [G]aze ",
-            "[U]p        [I]nventory   [H]elp ",
-            "[D]own      [T]ele[p]ort  [Q]uit ",
-            "            [C]opyright          ",
-        ];
+        let strs = self.help_rows();
+
+        let width = strs.iter().map(|s| s.len()).max().unwrap_or(0) as i32 + 10;
+
+        let rows = strs.len() as i32;
+
+        let w = self.popup(rows + 10, width.max(44));
 
         self.wcon(w, G::A_TITLE());
         self.mvwprintw_center(w, 2, "You can use the following commands");
@@ -24,17 +93,35 @@ impl G {
             self.mvwprintw_center_notrim(w, i as i32 + 4, s);
         }
 
-        wattron(w, A_REVERSE);
-        self.mvwprintw_center_notrim(w, 12, " Press any key ");
-        wattroff(w, A_REVERSE);
+        let movement = match self.keys.movement {
+            Movement::Arrows => "The arrow keys also move",
+            Movement::Vi => "The arrow keys and h j k l also move",
+            Movement::Keypad => "The arrow keys and keypad 8 4 6 2 also move",
+        };
 
-        box_(w, 0, 0);
-        wrefresh(w);
+        self.mvwprintw_center(w, rows + 5, movement);
 
-        getch();
+        self.mvwprintw_center(
+            w,
+            rows + 7,
+            &format!(
+                "{} for the map, or any other key",
+                self.keys
+                    .menuitem(KeyContext::Explore, Action::Legend, "Legend")
+            ),
+        );
 
-        G::popup_close(w);
+        self.ui.border(w);
+        self.ui.refresh(w);
 
-        self.redraw_underwins();
+        let key = self.getkey();
+
+        self.popup_close(w);
+
+        if self.keys.action(KeyContext::Explore, G::norm_key(key)) == Some(Action::Legend) {
+            self.legend();
+        } else {
+            self.redraw_underwins();
+        }
     }
 }
@@ -0,0 +1,241 @@
+//! Message history
+use crate::G;
+use crate::frontend::*;
+
+use wizardscastle::game::Direction;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Normal,
+    Good,
+    Bad,
+    Error,
+}
+
+/// A message that went to the log
+#[derive(Debug, Clone)]
+pub struct Message {
+    pub text: String,
+    pub turn: u32,
+    pub attr: Attr,
+    pub severity: Severity,
+}
+
+/// Width of the turn number column
+const TURN_WIDTH: usize = 7;
+
+impl Severity {
+    fn name(self) -> &'static str {
+        match self {
+            Severity::Normal => "normal",
+            Severity::Good => "good",
+            Severity::Bad => "bad",
+            Severity::Error => "error",
+        }
+    }
+
+    /// The next filter when cycling through them: all, then each severity
+    fn next_filter(filter: Option<Severity>) -> Option<Severity> {
+        match filter {
+            None => Some(Severity::Normal),
+            Some(Severity::Normal) => Some(Severity::Good),
+            Some(Severity::Good) => Some(Severity::Bad),
+            Some(Severity::Bad) => Some(Severity::Error),
+            Some(Severity::Error) => None,
+        }
+    }
+}
+
+impl G {
+    /// Lay out the history as screen rows, wrapping long lines
+    fn history_rows(&self, filter: Option<Severity>, width: usize) -> Vec<(String, Attr)> {
+        let width = width.saturating_sub(TURN_WIDTH).max(1);
+
+        let mut rows = Vec::new();
+
+        for m in self.messages.borrow().iter() {
+            if filter.is_some_and(|f| f != m.severity) {
+                continue;
+            }
+
+            let mut first = true;
+
+            for line in m.text.trim_end().lines() {
+                let chars: Vec<char> = line.chars().collect();
+
+                for chunk in chars
+                    .chunks(width)
+                    .chain(chars.is_empty().then_some(&[][..]))
+                {
+                    let turn = if first {
+                        format!("{:>5}  ", m.turn)
+                    } else {
+                        " ".repeat(TURN_WIDTH)
+                    };
+
+                    first = false;
+
+                    rows.push((
+                        format!("{}{}", turn, chunk.iter().collect::<String>()),
+                        m.attr,
+                    ));
+                }
+            }
+        }
+
+        rows
+    }
+
+    /// Find a row containing some text, case-insensitively, starting at
+    /// `from` and going backward (older) or forward (newer)
+    fn history_find(
+        rows: &[(String, Attr)],
+        text: &str,
+        from: usize,
+        backward: bool,
+    ) -> Option<usize> {
+        let text = text.to_lowercase();
+
+        let hit = |i: &usize| rows[*i].0[TURN_WIDTH..].to_lowercase().contains(&text);
+
+        if backward {
+            (0..=from.min(rows.len().checked_sub(1)?)).rev().find(hit)
+        } else {
+            (from..rows.len()).find(hit)
+        }
+    }
+
+    /// Show the message history
+    pub fn history(&self) {
+        let w = self.popup(self.ui.lines(), self.ui.cols());
+
+        let mut filter = None;
+        let mut top: Option<usize> = None; // None sticks to the newest
+        let mut search = String::new();
+        let mut hit: Option<usize> = None;
+        let mut status = String::new();
+
+        loop {
+            // The terminal might have changed size while waiting for a key
+            let lines = self.ui.lines();
+            let cols = self.ui.cols();
+
+            self.resize_popup(w, lines, cols);
+
+            let page = (lines - 7).max(1) as usize;
+
+            let rows = self.history_rows(filter, cols as usize - 4);
+
+            let max_top = rows.len().saturating_sub(page);
+            let t = top.unwrap_or(max_top).min(max_top);
+
+            self.ui.erase(w);
+
+            let filter_name = filter.map_or("all", |f: Severity| f.name());
+
+            self.wcon(w, G::A_TITLE());
+            self.mvwprintw_center(w, 1, &format!("Message history ({} messages)", filter_name));
+            self.wcoff(w, G::A_TITLE());
+
+            for (i, (text, attr)) in rows.iter().skip(t).take(page).enumerate() {
+                let highlight = if hit == Some(t + i) { A_REVERSE } else { 0 };
+
+                self.ui.attr_on(w, attr | highlight);
+                self.ui.mvaddstr(w, 3 + i as i32, 2, text);
+                self.ui.attr_off(w, attr | highlight);
+            }
+
+            if rows.is_empty() {
+                self.mvwprintw_center(w, 3, "No messages yet.");
+            }
+
+            self.ui.attr_on(w, A_BOLD);
+            self.ui.mvaddstr(w, lines - 3, 2, &status);
+            self.ui.attr_off(w, A_BOLD);
+
+            self.mvwprintw_center(
+                w,
+                lines - 2,
+                " |[Up]|/|[Down]| scroll  |[PgUp]|/|[PgDn]| page  |[/]| search  |[n]|/|[N]| again  |[f]|ilter  |[q]|uit ",
+            );
+
+            self.ui.border(w);
+            self.ui.refresh(w);
+
+            let key = self.getkey();
+
+            status.clear();
+
+            match self.key_dir(key) {
+                Some(Direction::North) => top = Some(t.saturating_sub(1)),
+                Some(Direction::South) => top = Some(t + 1),
+                _ => (),
+            }
+
+            let old_hit = hit;
+
+            match key {
+                KEY_PPAGE => top = Some(t.saturating_sub(page)),
+                KEY_NPAGE => top = Some(t + page),
+                KEY_HOME => top = Some(0),
+                KEY_END => top = None,
+                27 => break,
+                _ => match char::from_u32(key as u32) {
+                    Some('q' | 'Q') => break,
+                    Some('f' | 'F') => {
+                        filter = Severity::next_filter(filter);
+                        top = None;
+                        hit = None;
+                    }
+                    Some('/') => {
+                        self.ui.mvaddstr(w, lines - 3, 2, "Search: ");
+                        self.ui.show_cursor(true);
+                        search = self.read_line(w, (cols - 14) as usize).trim().to_string();
+                        self.ui.show_cursor(false);
+
+                        // Start from the bottom of the page
+                        hit = None;
+
+                        if !search.is_empty() {
+                            hit = G::history_find(&rows, &search, t + page - 1, true);
+                        }
+                    }
+                    Some(c @ ('n' | 'N')) if !search.is_empty() => {
+                        let backward = c == 'n';
+
+                        let from = match hit {
+                            Some(h) if backward => h.checked_sub(1),
+                            Some(h) => Some(h + 1),
+                            None => Some(t),
+                        };
+
+                        if let Some(h) =
+                            from.and_then(|f| G::history_find(&rows, &search, f, backward))
+                        {
+                            hit = Some(h);
+                        } else {
+                            status = format!("No more \"{}\"", search);
+                        }
+                    }
+                    _ => (),
+                },
+            }
+
+            if hit != old_hit
+                && let Some(h) = hit
+                && (h < t || h >= t + page)
+            {
+                // Bring the match into view
+                top = Some(h.saturating_sub(page / 2));
+            }
+
+            if key == '/' as i32 && hit.is_none() && !search.is_empty() {
+                status = format!("\"{}\" not found", search);
+            }
+        }
+
+        self.popup_close(w);
+
+        self.redraw_underwins();
+    }
+}
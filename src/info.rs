@@ -1,10 +1,10 @@
 use crate::G;
-use ncurses::*;
+use crate::frontend::*;
 
 impl G {
     /// Show info
     pub fn info(&self) {
-        let w = G::popup(15, 62);
+        let w = self.popup(15, 62);
 
         self.wcon(w, G::A_TITLE());
         self.mvwprintw_center(w, 2, "** WIZARD'S CASTLE **\n\n");
@@ -21,16 +21,16 @@ impl G {
             "https://github.com/beejjorgensen/Wizards-Castle-Info\n\n",
         );
 
-        wattron(w, A_REVERSE);
+        self.ui.attr_on(w, A_REVERSE);
         self.wprintw_center_notrim(w, " Press any key ");
-        wattroff(w, A_REVERSE);
+        self.ui.attr_off(w, A_REVERSE);
 
-        box_(w, 0, 0);
-        wrefresh(w);
+        self.ui.border(w);
+        self.ui.refresh(w);
 
-        getch();
+        self.getkey();
 
-        G::popup_close(w);
+        self.popup_close(w);
 
         self.redraw_underwins();
     }
@@ -1,5 +1,5 @@
 use crate::G;
-use ncurses::*;
+use crate::frontend::*;
 
 impl G {
     pub fn show_inventory(&self) {
@@ -34,7 +34,7 @@ impl G {
             "You have the following items:"
         };
 
-        let w = G::popup(height, width);
+        let w = self.popup(height, width);
 
         self.wcon(w, G::A_TITLE());
         self.mvwprintw_center(w, 2, &format!("{}\n\n", title));
@@ -42,25 +42,25 @@ impl G {
 
         for i in inv {
             self.wprintw_center_notrim(w, &format!("The {:<11}", i));
-            let _ = waddstr(w, "\n");
+            self.ui.addstr(w, "\n");
         }
 
         if player_has_magic_item {
-            wattr_on(w, A_BOLD);
+            self.ui.attr_on(w, A_BOLD);
             self.wprintw_center_notrim(w, &format!("The {:<11}", magic_item));
-            let _ = waddstr(w, "\n");
-            wattr_off(w, A_BOLD);
+            self.ui.addstr(w, "\n");
+            self.ui.attr_off(w, A_BOLD);
         }
 
-        wattron(w, A_REVERSE);
+        self.ui.attr_on(w, A_REVERSE);
         self.mvwprintw_center_notrim(w, height - 3, " Press any key ");
-        wattroff(w, A_REVERSE);
+        self.ui.attr_off(w, A_REVERSE);
 
-        box_(w, 0, 0);
-        wrefresh(w);
+        self.ui.border(w);
+        self.ui.refresh(w);
 
-        getch();
+        self.getkey();
 
-        G::popup_close(w);
+        self.popup_close(w);
     }
 }
@@ -0,0 +1,604 @@
+//! User-configurable key bindings
+//!
+//! Bindings are read from `keys.toml` in the user's config directory (see
+//! `Keymap::path()`). The file is a small subset of TOML: one section per
+//! input context, and one line per action:
+//!
+//!    [exploration]
+//!    north = "K"
+//!    help = ["H", "?"]
+//!
+//! There's also a `[settings]` section for options that aren't bindings,
+//! like `movement = "vi"` or `more = "off"`.
+//!
+//! Only the actions listed in the file are changed; everything else keeps
+//! its default binding.
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+/// Places in the game where keys are read
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyContext {
+    Explore,
+    Combat,
+    Spell,
+    Lamp,
+    Vendor,
+    Potions,
+    Teleport,
+}
+
+/// Which keys move the player, in addition to the arrow keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Movement {
+    Arrows,
+    Vi,
+    Keypad,
+}
+
+impl Movement {
+    /// Look up a movement scheme by the name used in settings and on the
+    /// command line
+    pub fn from_name(name: &str) -> Option<Movement> {
+        match name {
+            "arrows" => Some(Movement::Arrows),
+            "vi" => Some(Movement::Vi),
+            "keypad" => Some(Movement::Keypad),
+            _ => None,
+        }
+    }
+
+    /// The name used in settings and on the command line
+    pub fn name(self) -> &'static str {
+        match self {
+            Movement::Arrows => "arrows",
+            Movement::Vi => "vi",
+            Movement::Keypad => "keypad",
+        }
+    }
+
+    /// The keys `G::key_dir()` moves with besides the arrows, normalized,
+    /// and where they go
+    fn keys(self) -> &'static [(char, Action)] {
+        match self {
+            Movement::Arrows => &[],
+            Movement::Vi => &[
+                ('K', Action::North),
+                ('J', Action::South),
+                ('H', Action::West),
+                ('L', Action::East),
+            ],
+            Movement::Keypad => &[
+                ('8', Action::North),
+                ('2', Action::South),
+                ('4', Action::West),
+                ('6', Action::East),
+            ],
+        }
+    }
+}
+
+/// Named things a key can do
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    North,
+    South,
+    West,
+    East,
+    Up,
+    Down,
+    LevelUp,
+    LevelDown,
+    Attack,
+    Flare,
+    Lamp,
+    Gaze,
+    Open,
+    Read,
+    Inventory,
+    Trade,
+    Teleport,
+    Help,
+    Legend,
+    History,
+    Annotate,
+    Notes,
+    Look,
+    Rumors,
+    Bestiary,
+    Travel,
+    Explore,
+    Run,
+    Copyright,
+    Quit,
+    Bribe,
+    Cast,
+    Web,
+    Fireball,
+    Deathspell,
+    Nothing,
+    Leather,
+    Chainmail,
+    Plate,
+    Dagger,
+    Mace,
+    Sword,
+    Strength,
+    Intelligence,
+    Dexterity,
+}
+
+const CONTEXTS: [(KeyContext, &str); 7] = [
+    (KeyContext::Explore, "exploration"),
+    (KeyContext::Combat, "combat"),
+    (KeyContext::Spell, "spell"),
+    (KeyContext::Lamp, "lamp"),
+    (KeyContext::Vendor, "vendor"),
+    (KeyContext::Potions, "potions"),
+    (KeyContext::Teleport, "teleport"),
+];
+
+/// Contexts where the movement keys are checked before the bindings
+const MOVE_CONTEXTS: [KeyContext; 4] = [
+    KeyContext::Explore,
+    KeyContext::Combat,
+    KeyContext::Lamp,
+    KeyContext::Teleport,
+];
+
+const ACTIONS: [(Action, &str); 45] = [
+    (Action::North, "north"),
+    (Action::South, "south"),
+    (Action::West, "west"),
+    (Action::East, "east"),
+    (Action::Up, "up"),
+    (Action::Down, "down"),
+    (Action::LevelUp, "level-up"),
+    (Action::LevelDown, "level-down"),
+    (Action::Attack, "attack"),
+    (Action::Flare, "flare"),
+    (Action::Lamp, "lamp"),
+    (Action::Gaze, "gaze"),
+    (Action::Open, "open"),
+    (Action::Read, "read"),
+    (Action::Inventory, "inventory"),
+    (Action::Trade, "trade"),
+    (Action::Teleport, "teleport"),
+    (Action::Help, "help"),
+    (Action::Legend, "legend"),
+    (Action::History, "history"),
+    (Action::Annotate, "annotate"),
+    (Action::Notes, "notes"),
+    (Action::Look, "look"),
+    (Action::Rumors, "rumors"),
+    (Action::Bestiary, "bestiary"),
+    (Action::Travel, "travel"),
+    (Action::Explore, "explore"),
+    (Action::Run, "run"),
+    (Action::Copyright, "copyright"),
+    (Action::Quit, "quit"),
+    (Action::Bribe, "bribe"),
+    (Action::Cast, "cast"),
+    (Action::Web, "web"),
+    (Action::Fireball, "fireball"),
+    (Action::Deathspell, "deathspell"),
+    (Action::Nothing, "nothing"),
+    (Action::Leather, "leather"),
+    (Action::Chainmail, "chainmail"),
+    (Action::Plate, "plate"),
+    (Action::Dagger, "dagger"),
+    (Action::Mace, "mace"),
+    (Action::Sword, "sword"),
+    (Action::Strength, "strength"),
+    (Action::Intelligence, "intelligence"),
+    (Action::Dexterity, "dexterity"),
+];
+
+/// Default bindings, in the order they should be listed
+fn default_bindings(ctx: KeyContext) -> Vec<(Action, &'static str)> {
+    match ctx {
+        KeyContext::Explore => vec![
+            (Action::North, "N"),
+            (Action::South, "S"),
+            (Action::West, "W"),
+            (Action::East, "E"),
+            (Action::Up, "U"),
+            (Action::Down, "D"),
+            (Action::LevelUp, "["),
+            (Action::LevelDown, "]"),
+            (Action::Attack, "A"),
+            (Action::Flare, "F"),
+            (Action::Lamp, "L"),
+            (Action::Gaze, "G"),
+            (Action::Open, "O"),
+            (Action::Read, "R"),
+            (Action::Inventory, "I"),
+            (Action::Trade, "T"),
+            (Action::Teleport, "P"),
+            (Action::Help, "H?"),
+            (Action::Legend, "/"),
+            (Action::History, "M"),
+            (Action::Annotate, "="),
+            (Action::Notes, ":"),
+            (Action::Look, ";"),
+            (Action::Rumors, "Z"),
+            (Action::Bestiary, "B"),
+            (Action::Travel, "_"),
+            (Action::Explore, "X"),
+            (Action::Run, "."),
+            (Action::Copyright, "C"),
+            (Action::Quit, "Q"),
+        ],
+        KeyContext::Combat => vec![
+            (Action::Attack, "A"),
+            (Action::Bribe, "B"),
+            (Action::Cast, "C"),
+            (Action::North, "N"),
+            (Action::South, "S"),
+            (Action::West, "W"),
+            (Action::East, "E"),
+        ],
+        KeyContext::Spell => vec![
+            (Action::Web, "W"),
+            (Action::Fireball, "F"),
+            (Action::Deathspell, "D"),
+            (Action::Nothing, "N"),
+        ],
+        KeyContext::Lamp => vec![
+            (Action::North, "N"),
+            (Action::South, "S"),
+            (Action::West, "W"),
+            (Action::East, "E"),
+        ],
+        KeyContext::Vendor => vec![
+            (Action::Leather, "L"),
+            (Action::Chainmail, "C"),
+            (Action::Plate, "P"),
+            (Action::Dagger, "D"),
+            (Action::Mace, "M"),
+            (Action::Sword, "S"),
+            (Action::Lamp, "A"),
+            (Action::Nothing, "N"),
+        ],
+        KeyContext::Potions => vec![
+            (Action::Strength, "S"),
+            (Action::Intelligence, "I"),
+            (Action::Dexterity, "D"),
+            (Action::Nothing, "N"),
+        ],
+        KeyContext::Teleport => vec![(Action::Nothing, "N")],
+    }
+}
+
+pub struct Keymap {
+    bindings: HashMap<KeyContext, Vec<(Action, Vec<char>)>>,
+    pub movement: Movement,
+
+    /// Pause with --More-- before unread log lines scroll away
+    pub more: bool,
+}
+
+impl Keymap {
+    /// Build the default keymap
+    pub fn new() -> Keymap {
+        let mut bindings = HashMap::new();
+
+        for (ctx, _) in CONTEXTS.iter() {
+            let v = default_bindings(*ctx)
+                .into_iter()
+                .map(|(a, keys)| (a, keys.chars().collect()))
+                .collect();
+
+            bindings.insert(*ctx, v);
+        }
+
+        Keymap {
+            bindings,
+            movement: Movement::Arrows,
+            more: true,
+        }
+    }
+
+    /// Return the path to the user's keymap file
+    pub fn path() -> Option<PathBuf> {
+        if let Ok(dir) = env::var("XDG_CONFIG_HOME")
+            && !dir.is_empty()
+        {
+            return Some(PathBuf::from(dir).join("wizcasn").join("keys.toml"));
+        }
+
+        env::var("HOME")
+            .ok()
+            .map(|home| PathBuf::from(home).join(".config/wizcasn/keys.toml"))
+    }
+
+    /// Load the keymap from the user's keymap file, falling back to the
+    /// defaults if there is no file
+    pub fn load() -> Result<Keymap, String> {
+        let mut keymap = Keymap::new();
+
+        if let Some(path) = Keymap::path()
+            && path.exists()
+        {
+            let text =
+                fs::read_to_string(&path).map_err(|err| format!("{}: {}", path.display(), err))?;
+
+            keymap
+                .parse(&text)
+                .map_err(|err| format!("{}: {}", path.display(), err))?;
+        }
+
+        Ok(keymap)
+    }
+
+    /// Apply the contents of a keymap file on top of the current bindings
+    pub fn parse(&mut self, text: &str) -> Result<(), String> {
+        let mut ctx = None;
+        let mut in_settings = false;
+
+        for (i, raw_line) in text.lines().enumerate() {
+            let lineno = i + 1;
+            let line = Keymap::strip_comment(raw_line).trim();
+
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                let name = name.trim();
+
+                in_settings = name == "settings";
+
+                match CONTEXTS.iter().find(|(_, n)| *n == name) {
+                    Some((c, _)) => ctx = Some(*c),
+                    None if in_settings => ctx = None,
+                    None => return Err(format!("line {}: unknown section [{}]", lineno, name)),
+                }
+
+                continue;
+            }
+
+            let Some((name, value)) = line.split_once('=') else {
+                return Err(format!("line {}: expected `action = \"key\"`", lineno));
+            };
+
+            let name = name.trim();
+
+            if in_settings {
+                self.parse_setting(name, value.trim())
+                    .map_err(|err| format!("line {}: {}", lineno, err))?;
+                continue;
+            }
+
+            let Some(c) = ctx else {
+                return Err(format!("line {}: binding outside of a section", lineno));
+            };
+
+            let Some((action, _)) = ACTIONS.iter().find(|(_, n)| *n == name) else {
+                return Err(format!("line {}: unknown action `{}`", lineno, name));
+            };
+
+            let keys = Keymap::parse_keys(value.trim())
+                .map_err(|err| format!("line {}: {}", lineno, err))?;
+
+            let ctx_bindings = self.bindings.get_mut(&c).unwrap();
+
+            match ctx_bindings.iter_mut().find(|(a, _)| a == action) {
+                Some((_, k)) => *k = keys,
+                None => {
+                    return Err(format!(
+                        "line {}: action `{}` can't be used in [{}]",
+                        lineno,
+                        name,
+                        Keymap::context_name(c)
+                    ));
+                }
+            }
+        }
+
+        self.validate()
+    }
+
+    /// Apply a line from the `[settings]` section
+    fn parse_setting(&mut self, name: &str, value: &str) -> Result<(), String> {
+        let Some(value) = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) else {
+            return Err(format!("expected a quoted value, found {}", value));
+        };
+
+        match name {
+            "movement" => match Movement::from_name(value) {
+                Some(m) => self.movement = m,
+                None => return Err(format!("unknown movement scheme \"{}\"", value)),
+            },
+            "more" => match value {
+                "on" => self.more = true,
+                "off" => self.more = false,
+                _ => {
+                    return Err(format!(
+                        "expected \"on\" or \"off\" for more, found \"{}\"",
+                        value
+                    ));
+                }
+            },
+            _ => return Err(format!("unknown setting `{}`", name)),
+        }
+
+        Ok(())
+    }
+
+    /// Remove a trailing comment from a line, ignoring `#` inside quotes
+    fn strip_comment(line: &str) -> &str {
+        let mut quoted = false;
+
+        for (i, c) in line.char_indices() {
+            match c {
+                '"' => quoted = !quoted,
+                '#' if !quoted => return &line[..i],
+                _ => (),
+            }
+        }
+
+        line
+    }
+
+    /// Parse `"K"` or `["K", "L"]` into a list of keys
+    fn parse_keys(value: &str) -> Result<Vec<char>, String> {
+        let items: Vec<&str> =
+            if let Some(list) = value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+                list.split(',')
+                    .map(|s| s.trim())
+                    .filter(|s| !s.is_empty())
+                    .collect()
+            } else {
+                vec![value]
+            };
+
+        let mut keys = Vec::new();
+
+        for item in items {
+            let Some(s) = item.strip_prefix('"').and_then(|s| s.strip_suffix('"')) else {
+                return Err(format!("expected a quoted key, found {}", item));
+            };
+
+            let mut chars = s.chars();
+
+            match (chars.next(), chars.next()) {
+                // These are formatting characters for the centered printer
+                (Some('|'), None) | (Some('%'), None) => {
+                    return Err(format!("\"{}\" can't be used as a key", s));
+                }
+                (Some(c), None) => keys.push(Keymap::norm(c)),
+                _ => return Err(format!("\"{}\" is not a single key", s)),
+            }
+        }
+
+        if keys.is_empty() {
+            return Err(String::from("no keys given"));
+        }
+
+        Ok(keys)
+    }
+
+    /// Make sure no key does two things in the same context, and that the
+    /// movement keys don't hide any bindings
+    ///
+    /// Bindings left at their defaults are fine: the only ones the vi keys
+    /// touch are `H`elp and `L`amp, and those still work shifted.
+    pub fn validate(&self) -> Result<(), String> {
+        for (ctx, name) in CONTEXTS.iter() {
+            let mut seen: HashMap<char, Action> = HashMap::new();
+
+            for (action, keys) in &self.bindings[ctx] {
+                for k in keys {
+                    if let Some(other) = seen.insert(*k, *action)
+                        && other != *action
+                    {
+                        return Err(format!(
+                            "\"{}\" is bound to both `{}` and `{}` in [{}]",
+                            k,
+                            Keymap::action_name(other),
+                            Keymap::action_name(*action),
+                            name
+                        ));
+                    }
+                }
+            }
+        }
+
+        for (ctx, name) in CONTEXTS.iter().filter(|(c, _)| MOVE_CONTEXTS.contains(c)) {
+            let defaults = default_bindings(*ctx);
+
+            for (action, keys) in &self.bindings[ctx] {
+                if defaults
+                    .iter()
+                    .any(|(a, k)| a == action && k.chars().eq(keys.iter().copied()))
+                {
+                    continue;
+                }
+
+                for k in keys {
+                    if let Some((_, dir)) = self.movement.keys().iter().find(|(m, _)| m == k)
+                        && dir != action
+                    {
+                        return Err(format!(
+                            "\"{}\" moves {} with {} movement, so it can't be `{}` in [{}]",
+                            k,
+                            Keymap::action_name(*dir),
+                            self.movement.name(),
+                            Keymap::action_name(*action),
+                            name
+                        ));
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Keys are case-insensitive
+    fn norm(c: char) -> char {
+        c.to_uppercase().next().unwrap_or(c)
+    }
+
+    fn context_name(ctx: KeyContext) -> &'static str {
+        CONTEXTS.iter().find(|(c, _)| *c == ctx).unwrap().1
+    }
+
+    fn action_name(action: Action) -> &'static str {
+        ACTIONS.iter().find(|(a, _)| *a == action).unwrap().1
+    }
+
+    /// Look up the action for a normalized key
+    pub fn action(&self, ctx: KeyContext, key: char) -> Option<Action> {
+        self.bindings[&ctx]
+            .iter()
+            .find(|(_, keys)| keys.contains(&key))
+            .map(|(a, _)| *a)
+    }
+
+    /// Return the main key for an action
+    pub fn key(&self, ctx: KeyContext, action: Action) -> char {
+        match self.bindings[&ctx].iter().find(|(a, _)| *a == action) {
+            Some((_, keys)) => keys[0],
+            None => panic!("no binding for {:?} in {:?}", action, ctx),
+        }
+    }
+
+    /// Mark the key for an action inside its name, e.g. "[N]orth".
+    ///
+    /// If the key doesn't appear in the name, it's put in front instead:
+    /// "[K] North".
+    fn mark(&self, ctx: KeyContext, action: Action, name: &str, open: &str, close: &str) -> String {
+        let key = self.key(ctx, action);
+
+        match name.char_indices().find(|(_, c)| Keymap::norm(*c) == key) {
+            Some((i, c)) => format!(
+                "{}{}{}{}{}",
+                &name[..i],
+                open,
+                c,
+                close,
+                &name[i + c.len_utf8()..]
+            ),
+            None => format!("{}{}{} {}", open, key, close, name),
+        }
+    }
+
+    /// Return a menu item for an action, e.g. "|[N]|orth"
+    pub fn menuitem(&self, ctx: KeyContext, action: Action, name: &str) -> String {
+        self.mark(ctx, action, name, "|[", "]|")
+    }
+
+    /// Return a plain label for an action, e.g. "[N]orth"
+    pub fn label(&self, ctx: KeyContext, action: Action, name: &str) -> String {
+        self.mark(ctx, action, name, "[", "]")
+    }
+
+    /// Return just the key for an action as a menu item, e.g. "|[N]|"
+    pub fn keyitem(&self, ctx: KeyContext, action: Action) -> String {
+        format!("|[{}]|", self.key(ctx, action))
+    }
+}
@@ -0,0 +1,169 @@
+//! What the player has learned about rooms from afar
+//!
+//! Orb visions and lamp light are kept by room, so they can be looked up
+//! later without digging through the log. Lamps show what's really there,
+//! but orbs can lie, so what an orb showed is only a rumor until the player
+//! sees the room for themselves.
+use crate::G;
+use crate::keymap::{Action, KeyContext};
+
+use wizardscastle::room::RoomType;
+
+/// The map glyph for a vision of the Orb of Zot
+const ZOT_CHAR: char = 'Z';
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    Orb,
+    Lamp,
+}
+
+/// Something seen in a room without being there
+#[derive(Debug, Clone)]
+pub struct Sighting {
+    pub source: Source,
+    pub turn: u32,
+
+    /// What was seen, or `None` for the Orb of Zot
+    pub room: Option<RoomType>,
+}
+
+/// How a sighting stands up against what's known of the room now
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Truth {
+    Unconfirmed,
+    Confirmed,
+    Gone,
+    False,
+}
+
+impl Source {
+    pub fn name(self) -> &'static str {
+        match self {
+            Source::Orb => "Orb",
+            Source::Lamp => "Lamp",
+        }
+    }
+}
+
+impl Truth {
+    pub fn name(self) -> &'static str {
+        match self {
+            Truth::Unconfirmed => "unconfirmed",
+            Truth::Confirmed => "confirmed",
+            Truth::Gone => "gone now",
+            Truth::False => "false",
+        }
+    }
+}
+
+impl Sighting {
+    /// What was seen, in words
+    pub fn what(&self) -> String {
+        self.room
+            .as_ref()
+            .map_or(String::from("the Orb of Zot"), G::room_name)
+    }
+
+    /// What was seen, as it would be drawn on the map
+    pub fn glyph(&self) -> char {
+        self.room.as_ref().map_or(ZOT_CHAR, G::room_char)
+    }
+}
+
+impl G {
+    /// Remember something seen in a room
+    pub fn record_sighting(
+        &mut self,
+        x: u32,
+        y: u32,
+        z: u32,
+        source: Source,
+        room: Option<&RoomType>,
+    ) {
+        let turn = *self.game.turn();
+
+        self.sightings.entry((z, y, x)).or_default().push(Sighting {
+            source,
+            turn,
+            room: room.cloned(),
+        });
+    }
+
+    /// Everything seen in a room, oldest first
+    pub fn sightings_at(&self, x: u32, y: u32, z: u32) -> &[Sighting] {
+        self.sightings.get(&(z, y, x)).map_or(&[], |v| v.as_slice())
+    }
+
+    /// Check a sighting against the room it was of
+    ///
+    /// Rooms change as the game goes on (gold gets picked up, monsters get
+    /// killed), so a room that's empty now can't prove a sighting false. The
+    /// Orb of Zot looks like any other room, so visions of it stay rumors.
+    pub fn sighting_truth(&self, x: u32, y: u32, z: u32, s: &Sighting) -> Truth {
+        let r = self.game.dungeon_room_at(x, y, z);
+
+        if !r.discovered {
+            return match s.source {
+                Source::Lamp => Truth::Confirmed,
+                Source::Orb => Truth::Unconfirmed,
+            };
+        }
+
+        match &s.room {
+            None => Truth::Unconfirmed,
+            Some(rt) if G::room_name(rt) == G::room_name(&r.roomtype) => Truth::Confirmed,
+            Some(_) if matches!(r.roomtype, RoomType::Empty) => Truth::Gone,
+            Some(_) => Truth::False,
+        }
+    }
+
+    /// The latest sighting of a room the player hasn't seen, to mark it on
+    /// the map with
+    pub fn rumor_at(&self, x: u32, y: u32, z: u32) -> Option<&Sighting> {
+        if self.game.dungeon_room_at(x, y, z).discovered {
+            return None;
+        }
+
+        self.sightings_at(x, y, z).last()
+    }
+
+    /// List everything orbs and lamps have shown, oldest first
+    pub fn rumors_popup(&self) {
+        let mut all: Vec<(u32, u32, u32, &Sighting)> = self
+            .sightings
+            .iter()
+            .flat_map(|(&(z, y, x), v)| v.iter().map(move |s| (x, y, z, s)))
+            .collect();
+
+        all.sort_by_key(|&(_, _, _, s)| s.turn);
+
+        let rows: Vec<String> = all
+            .iter()
+            .map(|&(x, y, z, s)| {
+                format!(
+                    "T{:<4} Level {:<2} ({:>2},{:>2})  {:<4}  {:<22} {}",
+                    s.turn,
+                    z + 1,
+                    x + 1,
+                    y + 1,
+                    s.source.name(),
+                    s.what(),
+                    self.sighting_truth(x, y, z, s).name()
+                )
+            })
+            .collect();
+
+        self.list_popup(
+            "Rumors (orbs can lie, lamps can't)",
+            &format!(
+                "Nothing yet. {} into an orb or shine your {}.",
+                self.keys
+                    .menuitem(KeyContext::Explore, Action::Gaze, "Gaze"),
+                self.keys
+                    .menuitem(KeyContext::Explore, Action::Lamp, "Lamp")
+            ),
+            &rows,
+        );
+    }
+}
@@ -0,0 +1,145 @@
+//! The map legend: what each room symbol on the map means
+use crate::G;
+use crate::frontend::*;
+
+use wizardscastle::monster::MonsterType;
+use wizardscastle::room::RoomType;
+
+/// Rooms that are the same in every castle, in the order they're listed
+const PLAIN_ROOMS: [RoomType; 11] = [
+    RoomType::Empty,
+    RoomType::Entrance,
+    RoomType::StairsUp,
+    RoomType::StairsDown,
+    RoomType::Gold,
+    RoomType::Pool,
+    RoomType::Chest,
+    RoomType::Flares,
+    RoomType::Sinkhole,
+    RoomType::CrystalOrb,
+    RoomType::Book,
+];
+
+fn is_vendor(r: &RoomType) -> bool {
+    matches!(r, RoomType::Monster(m) if m.monster_type() == MonsterType::Vendor)
+}
+
+/// Tells if a room is of some kind
+type RoomTest = fn(&RoomType) -> bool;
+
+/// Rooms that differ from castle to castle, and what to call them
+const SPECIAL_ROOMS: [(RoomTest, &str); 4] = [
+    (|r| matches!(r, RoomType::Warp(_)), "a warp"),
+    (is_vendor, "a vendor"),
+    (
+        |r| matches!(r, RoomType::Monster(_)) && !is_vendor(r),
+        "a monster",
+    ),
+    (|r| matches!(r, RoomType::Treasure(_)), "a treasure"),
+];
+
+impl G {
+    /// Find a room in the castle that matches, to see how it's drawn
+    fn example_room(&self, pred: RoomTest) -> Option<RoomType> {
+        let (xs, ys, zs) = self.castle_size();
+
+        (0..zs)
+            .flat_map(|z| (0..ys).flat_map(move |y| (0..xs).map(move |x| (x, y, z))))
+            .map(|(x, y, z)| &self.game.dungeon_room_at(x, y, z).roomtype)
+            .find(|r| pred(r))
+            .cloned()
+    }
+
+    /// The map's room glyphs and what they mean
+    ///
+    /// Rooms that carry something particular, like monsters, are looked up
+    /// in the castle so they're drawn just like they are on the map.
+    fn legend_rows(&self) -> Vec<(char, String)> {
+        let mut rows: Vec<(char, String)> = PLAIN_ROOMS
+            .iter()
+            .map(|r| (G::room_char(r), G::room_name(r)))
+            .collect();
+
+        for (pred, name) in SPECIAL_ROOMS.iter() {
+            if let Some(r) = self.example_room(*pred) {
+                rows.push((G::room_char(&r), name.to_string()));
+            }
+        }
+
+        rows
+    }
+
+    /// Show what the symbols on the map mean
+    pub fn legend(&self) {
+        let rows = self.legend_rows();
+
+        // Two columns, plus the unknown room, a rumor, a note, and the player
+        let per_col = (rows.len() as i32 + 4 + 1) / 2;
+
+        let w = self.popup(per_col + 8, 64);
+
+        self.wcon(w, G::A_TITLE());
+        self.mvwprintw_center(w, 2, "Map legend");
+        self.wcoff(w, G::A_TITLE());
+
+        let pos = |i: i32| (4 + i % per_col, 5 + (i / per_col) * 30);
+
+        for (i, (ch, name)) in rows.iter().enumerate() {
+            let (y, x) = pos(i as i32);
+            let attr_str = format!("room-{}", ch);
+
+            self.ui.mv(w, y, x + 1);
+            self.wcon(w, &attr_str);
+            self.ui.addch(w, *ch);
+            self.wcoff(w, &attr_str);
+
+            self.ui.mvaddstr(w, y, x + 5, &G::initial_upper(name));
+        }
+
+        let (y, x) = pos(rows.len() as i32);
+
+        self.ui.mv(w, y, x + 1);
+        self.ui.attr_on(w, A_DIM);
+        self.ui.addch(w, '?');
+        self.ui.attr_off(w, A_DIM);
+        self.ui.mvaddstr(w, y, x + 5, "A room you haven't seen");
+
+        let (y, x) = pos(rows.len() as i32 + 1);
+
+        self.ui.mv(w, y, x + 1);
+        self.wcon(w, "rumor");
+        self.ui.addch(w, 'G');
+        self.wcoff(w, "rumor");
+        self.ui
+            .mvaddstr(w, y, x + 5, "What an orb showed you there");
+
+        let (y, x) = pos(rows.len() as i32 + 2);
+
+        self.ui.mv(w, y, x + 2);
+        self.wcon(w, "note");
+        self.ui.addch(w, '*');
+        self.wcoff(w, "note");
+        self.ui.mvaddstr(w, y, x + 5, "A room you made a note on");
+
+        let (y, x) = pos(rows.len() as i32 + 3);
+
+        self.ui.mv(w, y, x);
+        self.ui.attr_on(w, A_BOLD);
+        self.ui.addstr(w, "< >");
+        self.ui.attr_off(w, A_BOLD);
+        self.ui.mvaddstr(w, y, x + 5, "You are here");
+
+        self.ui.attr_on(w, A_REVERSE);
+        self.mvwprintw_center_notrim(w, per_col + 5, " Press any key ");
+        self.ui.attr_off(w, A_REVERSE);
+
+        self.ui.border(w);
+        self.ui.refresh(w);
+
+        self.getkey();
+
+        self.popup_close(w);
+
+        self.redraw_underwins();
+    }
+}
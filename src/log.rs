@@ -1,5 +1,7 @@
 use crate::G;
-use ncurses::*;
+use crate::frontend::*;
+use crate::history::{Message, Severity};
+use crate::win::LOG_Y;
 
 impl G {
     #[allow(non_snake_case)]
@@ -17,25 +19,112 @@ impl G {
     }
 
     pub fn update_log_good(&self, s: &str) {
-        self.update_log_attr(s, self.wcget(G::A_LOG_GOOD()));
+        self.log_message(s, self.wcget(G::A_LOG_GOOD()), Severity::Good);
     }
 
     pub fn update_log_bad(&self, s: &str) {
-        self.update_log_error(s)
+        self.log_message(s, self.wcget(G::A_LOG_ERROR()), Severity::Bad);
     }
 
     pub fn update_log_error(&self, s: &str) {
-        self.update_log_attr(s, self.wcget(G::A_LOG_ERROR()));
+        self.log_message(s, self.wcget(G::A_LOG_ERROR()), Severity::Error);
     }
 
     pub fn update_log_attr(&self, s: &str, attr: u32) {
-        wattr_on(self.loginner, attr);
-        let _ = waddstr(self.loginner, &format!("\n{}", s));
-        wattr_off(self.loginner, attr);
+        self.log_message(s, attr, Severity::Normal);
+    }
+
+    /// Add a message to the history and show it in the log
+    ///
+    /// It goes in the history only after any --More--, or a resize while
+    /// waiting there would redraw it, and then it'd be printed twice.
+    fn log_message(&self, s: &str, attr: u32, severity: Severity) {
+        self.more(s);
+
+        self.messages.borrow_mut().push(Message {
+            text: s.to_string(),
+            turn: *self.game.turn(),
+            attr,
+            severity,
+        });
+
+        self.print_log(s, attr);
+
+        self.ui.border(self.logwin);
+
+        self.ui.refresh(self.loginner);
+        self.ui.refresh(self.logwin);
+    }
+
+    /// Pause with --More-- if printing `s` would scroll lines away before
+    /// they've been read
+    ///
+    /// Lines count as read once a key is pressed.
+    fn more(&self, s: &str) {
+        if !self.keys.more {
+            return;
+        }
+
+        let height = (self.ui.lines() - LOG_Y - 1).max(1) as usize;
+        let width = self.ui.maxx(self.loginner).max(1) as usize;
+
+        // Every message starts on a new line, and long lines wrap
+        let needed: usize = s
+            .split('\n')
+            .map(|l| l.chars().count().div_ceil(width).max(1))
+            .sum();
+
+        let unseen = self.log_unseen.get();
+
+        if unseen > 0 && unseen + needed > height {
+            loop {
+                let bottom = self.ui.lines() - LOG_Y - 1;
+
+                self.ui.attr_on(self.logwin, A_REVERSE);
+                self.ui.mvaddstr(self.logwin, bottom, 2, "--More--");
+                self.ui.attr_off(self.logwin, A_REVERSE);
+                self.ui.refresh(self.logwin);
+
+                match self.ui.getch(-1) {
+                    Some(KEY_RESIZE) => self.handle_resize(),
+                    Some(_) => break,
+                    None => (),
+                }
+            }
+
+            self.ui.border(self.logwin);
+            self.ui.refresh(self.logwin);
+
+            self.log_unseen.set(0);
+        }
+
+        self.log_unseen.set(self.log_unseen.get() + needed);
+    }
+
+    fn print_log(&self, s: &str, attr: u32) {
+        self.ui.attr_on(self.loginner, attr);
+        self.ui.addstr(self.loginner, &format!("\n{}", s));
+        self.ui.attr_off(self.loginner, attr);
+    }
+
+    /// Print the log again from scratch, like after a resize
+    pub fn redraw_log(&self) {
+        self.ui.erase(self.logwin);
+        self.ui.mv(self.loginner, 0, 0);
+
+        let messages = self.messages.borrow();
+
+        // Each message takes at least a line, so this is plenty
+        let height = (self.ui.lines() - LOG_Y) as usize;
+        let start = messages.len().saturating_sub(height);
+
+        for m in &messages[start..] {
+            self.print_log(&m.text, m.attr);
+        }
 
-        box_(self.logwin, 0, 0);
+        self.ui.border(self.logwin);
 
-        wrefresh(self.loginner);
-        wrefresh(self.logwin);
+        self.ui.refresh(self.loginner);
+        self.ui.refresh(self.logwin);
     }
 }
@@ -0,0 +1,160 @@
+//! Moving a cursor around the map, to look at rooms or pick one
+use crate::G;
+use crate::frontend::*;
+use crate::keymap::{Action, KeyContext};
+use crate::knowledge::Truth;
+
+use wizardscastle::game::Direction;
+
+/// Widest text that fits in the status window
+const STAT_TEXT: usize = 28;
+
+/// Where the map cursor is, and what it's for
+#[derive(Debug, Clone, Copy)]
+pub struct MapCursor {
+    pub x: u32,
+    pub y: u32,
+    pub picking: bool,
+}
+
+impl G {
+    /// Move a cursor around the map, starting at the player
+    ///
+    /// The level keys go between levels. When looking, the status window
+    /// describes the room under the cursor, and there's nothing to pick.
+    /// Otherwise `ENTER` picks a room. Returns `None` if the player backs
+    /// out.
+    pub fn pick_room(&mut self, look: bool) -> Option<(u32, u32, u32)> {
+        let (xs, ys, zs) = self.castle_size();
+
+        let mut x = self.game.player_x();
+        let mut y = self.game.player_y();
+        let mut z = self.game.player_z();
+
+        let picked = loop {
+            self.map_cursor = Some(MapCursor {
+                x,
+                y,
+                picking: !look,
+            });
+            self.map_z = Some(z);
+
+            self.update_map(self.options.discover_all);
+
+            if look {
+                self.update_stat_look(x, y, z);
+            }
+
+            let key = self.getkey();
+
+            let enter = key == '\n' as i32 || key == KEY_ENTER;
+
+            match self.key_dir(key) {
+                Some(Direction::North) => y = y.saturating_sub(1),
+                Some(Direction::South) => y = (y + 1).min(ys - 1),
+                Some(Direction::West) => x = x.saturating_sub(1),
+                Some(Direction::East) => x = (x + 1).min(xs - 1),
+                None => match self.browse_dir(key) {
+                    Some(dir) => z = (z as i32 + dir).clamp(0, zs as i32 - 1) as u32,
+                    None if enter && !look => break Some((x, y, z)),
+                    None if enter || key == 27 => break None,
+                    None if look
+                        && self.keys.action(KeyContext::Explore, G::norm_key(key))
+                            == Some(Action::Look) =>
+                    {
+                        break None;
+                    }
+                    None => (),
+                },
+            }
+        };
+
+        self.map_cursor = None;
+        self.map_z = None;
+
+        self.update_map(self.options.discover_all);
+
+        if look {
+            self.update_stat();
+        }
+
+        picked
+    }
+
+    /// Look around the map
+    pub fn look(&mut self) {
+        self.pick_room(true);
+    }
+
+    /// Describe the room under the look cursor in the status window
+    ///
+    /// Notes are the player's own text, so they're printed as is rather
+    /// than through `mvwprintw_center()`.
+    fn update_stat_look(&self, x: u32, y: u32, z: u32) {
+        let w = self.statwin;
+
+        self.ui.erase(w);
+
+        self.wcon(w, G::A_TITLE());
+        self.mvwprintw_center(w, 1, "Looking");
+        self.wcoff(w, G::A_TITLE());
+
+        self.mvwprintw_center(w, 3, &format!("({},{}) level {}", x + 1, y + 1, z + 1));
+
+        let here = (x, y, z)
+            == (
+                self.game.player_x(),
+                self.game.player_y(),
+                self.game.player_z(),
+            );
+
+        let r = self.game.dungeon_room_at(x, y, z);
+
+        let desc = if r.discovered || self.options.discover_all {
+            G::initial_upper(&G::room_name(&r.roomtype))
+        } else {
+            String::from("Unexplored")
+        };
+
+        self.mvwprintw_center(w, 4, &desc);
+
+        if here {
+            self.mvwprintw_center(w, 5, "(You are here)");
+        }
+
+        let mut row = 7;
+
+        for s in self.sightings_at(x, y, z).iter().rev().take(4) {
+            // Only the first 28 columns fit, so rumors just get a ?
+            let what = match self.sighting_truth(x, y, z, s) {
+                Truth::Confirmed => s.what(),
+                Truth::Unconfirmed => format!("{}?", s.what()),
+                t => format!("{} ({})", s.what(), t.name()),
+            };
+
+            let line = format!("{} T{}: {}", s.source.name(), s.turn, what);
+
+            self.ui
+                .mvaddstr(w, row, 2, &line.chars().take(STAT_TEXT).collect::<String>());
+
+            row += 1;
+        }
+
+        if let Some(n) = self.note_at(x, y, z) {
+            let line = format!("Note: {} {}", n.mark, n.text);
+
+            row += 1;
+
+            for chunk in line.chars().collect::<Vec<_>>().chunks(STAT_TEXT).take(3) {
+                self.ui
+                    .mvaddstr(w, row, 2, &chunk.iter().collect::<String>());
+                row += 1;
+            }
+        }
+
+        self.mvwprintw_center(w, 15, "|[ESC]| to stop looking");
+
+        self.ui.border(w);
+        self.ui.refresh(w);
+    }
+}
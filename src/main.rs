@@ -1,6 +1,6 @@
-use ncurses::*;
+use std::cell::{Cell, RefCell};
 use std::char;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::env;
 
 use rand::RngExt;
@@ -13,22 +13,60 @@ use wizardscastle::game::{
     OrbEvent, RandomMessage, Stairs,
 };
 use wizardscastle::monster::MonsterType;
+use wizardscastle::player::Stat;
 use wizardscastle::room::RoomType;
 
+use crate::achievements::Achievement;
+use crate::bestiary::{Bestiary, DISHES};
+use crate::career::Tally;
+use crate::curses::Curses;
+use crate::fight::Fight;
+use crate::frontend::*;
+use crate::history::Message;
+use crate::keymap::{Action, KeyContext, Keymap, Movement};
+use crate::knowledge::{Sighting, Source};
+use crate::look::MapCursor;
+use crate::notes::Note;
+use crate::scores::Ending;
+use crate::size::{DEFAULT_SIZE, MAX_SIDE, MIN_SIDE, Size};
 use crate::stat::StatMode;
+use crate::travel::Travel;
+use crate::win::{LOG_Y, MAP_COLS, STAT_COLS};
 
+mod achievements;
+mod atlas;
+mod bestiary;
 mod bribe;
+mod career;
 mod chargen;
+mod curses;
+mod explore;
+mod fight;
+mod frontend;
 mod gameover;
+#[cfg_attr(not(test), allow(dead_code))] // Only used by the tests
+mod headless;
 mod help;
+mod history;
 mod info;
 mod inv;
+mod keymap;
+mod knowledge;
+mod legend;
 mod log;
+mod look;
 mod map;
+mod menu;
 mod names;
+mod notes;
 mod quit;
+mod scores;
+mod size;
 mod stat;
 mod teleport;
+#[cfg(test)]
+mod tests;
+mod travel;
 mod vendor;
 mod win;
 
@@ -38,16 +76,36 @@ struct Opts {
     give_orb_of_zot: bool,
     give_runestaff: bool,
     locations: bool,
+    movement: Option<Movement>,
+    size: Option<Size>,
+    scores: Option<String>,
+    profile: Option<String>,
+    data_dir: Option<String>,
 }
 
 struct G {
-    color: HashMap<&'static str, attr_t>,
+    ui: Box<dyn Frontend>,
+    color: HashMap<&'static str, Attr>,
     game: Game,
-    mapwin: WINDOW,
-    statwin: WINDOW,
-    logwin: WINDOW,
-    loginner: WINDOW,
+    mapwin: Win,
+    statwin: Win,
+    logwin: Win,
+    loginner: Win,
+    atlaswin: Cell<Option<Win>>,
+    popups: RefCell<Vec<(Win, i32, i32)>>,
+    messages: RefCell<Vec<Message>>,
+    log_unseen: Cell<usize>,
+    in_castle: bool,
+    map_z: Option<u32>,
+    map_cursor: Option<MapCursor>,
+    notes: BTreeMap<(u32, u32, u32), Note>,
+    sightings: BTreeMap<(u32, u32, u32), Vec<Sighting>>,
+    travel: Option<Travel>,
+    tally: Tally,
+    beasts: Bestiary,
+    fight: Option<Fight>,
     statmode: StatMode,
+    keys: Keymap,
 
     rng: ThreadRng,
 
@@ -58,40 +116,35 @@ struct G {
 
 impl G {
     /// Build a new global game struct
-    fn new(options: Opts) -> G {
+    fn new(ui: Box<dyn Frontend>, options: Opts, keys: Keymap) -> G {
         // Build out the known color schemes for color and non-color terminals
         let mut color = HashMap::new();
 
-        if has_colors() {
-            init_pair(1, COLOR_YELLOW, COLOR_BLACK);
-            init_pair(2, COLOR_RED, COLOR_BLACK);
-            init_pair(3, COLOR_GREEN, COLOR_BLACK);
-            init_pair(4, COLOR_BLUE, COLOR_BLACK);
-            init_pair(5, COLOR_MAGENTA, COLOR_BLACK);
-            init_pair(6, COLOR_CYAN, COLOR_BLACK);
-
-            color.insert("bold-yellow", COLOR_PAIR(1) | A_BOLD);
-            color.insert("bold-red", COLOR_PAIR(2) | A_BOLD);
-            color.insert("red", COLOR_PAIR(2));
-            color.insert("dim-yellow", COLOR_PAIR(1) | A_DIM);
-            color.insert("dim-green", COLOR_PAIR(3) | A_DIM);
-            color.insert("dim-red", COLOR_PAIR(2) | A_DIM);
+        if ui.has_colors() {
+            color.insert("bold-yellow", YELLOW | A_BOLD);
+            color.insert("bold-red", RED | A_BOLD);
+            color.insert("red", RED);
+            color.insert("dim-yellow", YELLOW | A_DIM);
+            color.insert("dim-green", GREEN | A_DIM);
+            color.insert("dim-red", RED | A_DIM);
 
             color.insert("room-.", A_DIM); // Empis [sic]
             color.insert("room-E", 0);
             color.insert("room-D", 0);
             color.insert("room-U", 0);
-            color.insert("room-G", COLOR_PAIR(1) | A_BOLD);
-            color.insert("room-P", COLOR_PAIR(4) | A_BOLD);
+            color.insert("room-G", YELLOW | A_BOLD);
+            color.insert("room-P", BLUE | A_BOLD);
             color.insert("room-C", 0);
             color.insert("room-F", A_BOLD);
             color.insert("room-W", 0);
             color.insert("room-S", 0);
-            color.insert("room-O", COLOR_PAIR(6));
+            color.insert("room-O", CYAN);
             color.insert("room-B", 0);
             color.insert("room-V", 0);
-            color.insert("room-M", COLOR_PAIR(2) | A_BOLD);
-            color.insert("room-T", COLOR_PAIR(1) | A_BOLD);
+            color.insert("room-M", RED | A_BOLD);
+            color.insert("room-T", YELLOW | A_BOLD);
+            color.insert("note", CYAN | A_BOLD);
+            color.insert("rumor", GREEN | A_DIM);
         } else {
             color.insert("bold-yellow", A_BOLD);
             color.insert("bold-red", 0);
@@ -115,24 +168,49 @@ impl G {
             color.insert("room-V", 0);
             color.insert("room-M", 0);
             color.insert("room-T", A_BOLD);
+            color.insert("note", A_BOLD);
+            color.insert("rumor", A_DIM);
         }
 
-        let logwin = newwin(8, 80, 17, 0);
-        let loginner = derwin(logwin, 7, 78, 0, 1);
+        G::wait_for_size(&*ui);
 
-        scrollok(loginner, true);
+        let lines = ui.lines();
+        let cols = ui.cols();
 
-        let game = Game::new(8, 8, 8);
+        let logwin = ui.newwin(lines - LOG_Y, cols, LOG_Y, 0);
+        let loginner = ui.derwin(logwin, lines - LOG_Y - 1, cols - 2, 0, 1);
+
+        ui.scrollok(loginner, true);
+
+        let mapwin = ui.newwin(LOG_Y, MAP_COLS, 0, 0);
+        let statwin = ui.newwin(LOG_Y, STAT_COLS, 0, MAP_COLS + 1);
+
+        let game = Game::new(DEFAULT_SIZE.0, DEFAULT_SIZE.1, DEFAULT_SIZE.2);
 
         // Return the new struct
         let mut g = G {
+            ui,
             color,
-            mapwin: newwin(17, 47, 0, 0),
-            statwin: newwin(17, 32, 0, 48),
+            mapwin,
+            statwin,
             logwin,
             loginner,
+            atlaswin: Cell::new(None),
+            popups: RefCell::new(Vec::new()),
+            messages: RefCell::new(Vec::new()),
+            log_unseen: Cell::new(0),
+            in_castle: false,
+            map_z: None,
+            map_cursor: None,
+            notes: BTreeMap::new(),
+            sightings: BTreeMap::new(),
+            travel: None,
+            tally: Tally::default(),
+            beasts: Bestiary::default(),
+            fight: None,
             game,
             statmode: StatMode::None,
+            keys,
 
             retreat_direction: None,
 
@@ -141,6 +219,7 @@ impl G {
             options,
         };
 
+        g.layout();
         g.restart(false);
 
         g
@@ -149,7 +228,12 @@ impl G {
     /// Restore the game to a clean slate for restarting
     fn restart(&mut self, new_game: bool) {
         if new_game {
-            self.game = Game::new(8, 8, 8);
+            let (x, y, z) = self.options.size.unwrap_or_else(|| self.choose_size());
+
+            self.game = Game::new(x, y, z);
+
+            // The atlas might fit this castle better or worse
+            self.layout();
         }
 
         if self.options.give_orb_of_zot {
@@ -160,8 +244,16 @@ impl G {
             self.game.debug_give_runestaff();
         }
 
-        werase(self.loginner);
-        wmove(self.loginner, 0, 0);
+        self.ui.erase(self.loginner);
+        self.ui.mv(self.loginner, 0, 0);
+        self.messages.borrow_mut().clear();
+        self.notes.clear();
+        self.sightings.clear();
+        self.travel = None;
+        self.tally = Tally::default();
+        self.beasts = Bestiary::default();
+        self.fight = None;
+        self.log_unseen.set(0);
     }
 
     #[allow(non_snake_case)]
@@ -174,27 +266,59 @@ impl G {
         "bold-red"
     }
 
-    /// Normalize an input character from getch(), making it uppercase
+    /// Normalize an input character from getkey(), making it uppercase
+    ///
+    /// Arrow keys come back as a space. Use `key_dir()` to see if a key is
+    /// a movement key.
     fn norm_key(key: i32) -> char {
-        let mut ch = ' ';
-
-        if G::is_arrow_key(key) {
-            match key {
-                KEY_UP => ch = 'n',
-                KEY_DOWN => ch = 's',
-                KEY_LEFT => ch = 'w',
-                KEY_RIGHT => ch = 'e',
-                _ => (),
-            }
+        let ch = if G::is_arrow_key(key) {
+            ' '
         } else {
-            ch = char::from_u32(key as u32).unwrap()
-        }
+            char::from_u32(key as u32).unwrap()
+        };
 
         let v: Vec<_> = ch.to_uppercase().collect();
 
         v[0]
     }
 
+    /// Translate a key into a direction, according to the movement scheme
+    ///
+    /// The arrow keys always move. Vi keys are lowercase only so that the
+    /// shifted letters are still available for commands like `H`elp and
+    /// `L`amp.
+    fn key_dir(&self, key: i32) -> Option<Direction> {
+        match key {
+            KEY_UP => return Some(Direction::North),
+            KEY_DOWN => return Some(Direction::South),
+            KEY_LEFT => return Some(Direction::West),
+            KEY_RIGHT => return Some(Direction::East),
+            _ => (),
+        }
+
+        let ch = char::from_u32(key as u32)?;
+
+        match (self.keys.movement, ch) {
+            (Movement::Vi, 'k') | (Movement::Keypad, '8') => Some(Direction::North),
+            (Movement::Vi, 'j') | (Movement::Keypad, '2') => Some(Direction::South),
+            (Movement::Vi, 'h') | (Movement::Keypad, '4') => Some(Direction::West),
+            (Movement::Vi, 'l') | (Movement::Keypad, '6') => Some(Direction::East),
+            _ => None,
+        }
+    }
+
+    /// Look up the action for a key in a context where the player can pick a
+    /// direction
+    fn key_action(&self, ctx: KeyContext, key: i32) -> Option<Action> {
+        match self.key_dir(key) {
+            Some(Direction::North) => Some(Action::North),
+            Some(Direction::South) => Some(Action::South),
+            Some(Direction::West) => Some(Action::West),
+            Some(Direction::East) => Some(Action::East),
+            None => self.keys.action(ctx, G::norm_key(key)),
+        }
+    }
+
     /// Move a direction
     fn move_dir(&mut self, dir: Direction) {
         // Ask if player is sure they want to leave
@@ -272,19 +396,21 @@ impl G {
 
         self.set_statmode(StatMode::Lamp);
 
-        let key = getch();
+        let key = self.getkey();
 
-        let dir = match G::norm_key(key) {
-            'N' => Some(Direction::North),
-            'S' => Some(Direction::South),
-            'W' => Some(Direction::West),
-            'E' => Some(Direction::East),
+        let dir = match self.key_action(KeyContext::Lamp, key) {
+            Some(Action::North) => Some(Direction::North),
+            Some(Action::South) => Some(Direction::South),
+            Some(Action::West) => Some(Direction::West),
+            Some(Action::East) => Some(Direction::East),
             _ => None,
         };
 
         if let Some(d) = dir {
             match self.game.shine_lamp(d) {
                 Ok((x, y, z, room_type)) => {
+                    self.record_sighting(x, y, z, Source::Lamp, Some(&room_type));
+
                     self.update_log(&format!(
                         "The lamp shines into ({},{}) level {}. There you'll find {}.",
                         x + 1,
@@ -382,6 +508,8 @@ impl G {
                     );
                 }
                 OrbEvent::Item(room_type, x, y, z) => {
+                    self.record_sighting(x, y, z, Source::Orb, Some(&room_type));
+
                     s = format!(
                         "{} at ({},{}) level {}",
                         G::room_name(&room_type),
@@ -391,6 +519,8 @@ impl G {
                     );
                 }
                 OrbEvent::OrbOfZot(x, y, z) => {
+                    self.record_sighting(x, y, z, Source::Orb, None);
+
                     s = format!("THE ORB OF ZOT at ({},{}) level {}!", x + 1, y + 1, z + 1);
                     logf = G::update_log_good;
                 }
@@ -423,6 +553,7 @@ impl G {
                 ChestEvent::Explode => self.update_log_bad("KABOOM! It explodes!"),
                 ChestEvent::Gas => self.update_log_bad("Gas! You stagger from the room."),
                 ChestEvent::Treasure(amount) => {
+                    self.tally.gold(amount);
                     self.update_log(&format!("You find {} gold pieces!", amount))
                 }
             },
@@ -519,6 +650,7 @@ impl G {
         // Cure blindness
         if self.game.cure_blindness() {
             self.update_log_good("The Opal Eye cures your blindness!");
+            self.achieve(Achievement::OpalEye);
         }
 
         // Cure book stuck to hands
@@ -586,6 +718,10 @@ impl G {
 
     /// Print messaging when monster defeated by melee or magic
     fn monster_defeated_message(&mut self, result: HitResult, mon_art: &str, m_str: &str) {
+        self.tally
+            .kill(m_str, result.killed_vendor, result.treasure);
+        self.beasts.entry(m_str).kills += 1;
+
         self.update_log_good(&format!(
             "{} {} lies dead at your feet!",
             G::initial_upper(mon_art),
@@ -593,13 +729,12 @@ impl G {
         ));
 
         if self.game.rand_recipe() {
-            let suffix = [
-                "wich", " stew", " soup", " burger", " roast", " munchy", " taco", " pie",
-            ];
+            let i = self.rng.random_range(0..DISHES.len());
+            let dish = format!("{}{}", m_str, DISHES[i]);
 
-            let i = self.rng.random_range(0..suffix.len());
+            self.update_log(&format!("You spend an hour eating {}.", dish));
 
-            self.update_log(&format!("You spend an hour eating {}{}.", m_str, suffix[i]));
+            self.beasts.dishes.insert(dish);
         }
 
         if result.killed_vendor {
@@ -614,6 +749,8 @@ impl G {
             }
 
             self.update_log(&s);
+
+            self.achieve(Achievement::VendorKiller);
         } else {
             if result.got_runestaff {
                 self.update_log_good("** GREAT ZOT! YOU'VE FOUND THE RUNESTAFF! **");
@@ -648,7 +785,12 @@ impl G {
             }
 
             Ok(CombatEvent::Hit(result)) => {
-                self.update_log_good(&format!("You hit the lousy {}!", mon_str));
+                self.update_log_good(&format!(
+                    "You hit the lousy {} for {} damage!",
+                    mon_str, result.damage
+                ));
+                self.beasts.entry(mon_str).dealt += result.damage as u64;
+                self.fight_hit(result.damage);
 
                 if result.broke_weapon {
                     self.update_log_bad(&format!(
@@ -665,6 +807,7 @@ impl G {
 
             Ok(CombatEvent::Miss) => {
                 self.update_log_bad("Drat! Missed!");
+                self.fight_miss();
             }
 
             Ok(any) => panic!("unexpected combat event {:#?}", any),
@@ -686,29 +829,38 @@ impl G {
     }
 
     /// Combat: player spell
-    fn combat_player_attack_spell(&mut self, mon_str: &str, mon_art: &str) -> bool {
+    fn combat_player_attack_spell(
+        &mut self,
+        monster_type: MonsterType,
+        mon_str: &str,
+        mon_art: &str,
+    ) -> bool {
         let mut valid = false;
         let mut done = false;
 
         self.set_statmode(StatMode::Spell);
 
         while !valid {
-            let ch = G::norm_key(getch());
+            let action = self
+                .keys
+                .action(KeyContext::Spell, G::norm_key(self.getkey()));
 
-            match ch {
-                'W' => match self.game.spell_web() {
+            match action {
+                Some(Action::Web) => match self.game.spell_web() {
                     Ok(CombatEvent::Hit(_)) => (),
                     Ok(CombatEvent::Died) => (),
                     Ok(any) => panic!("Unexpected: {:#?}", any),
                     Err(err) => panic!("{:#?}", err),
                 },
 
-                'F' => match self.game.spell_fireball() {
+                Some(Action::Fireball) => match self.game.spell_fireball() {
                     Ok(CombatEvent::Hit(hr)) => {
                         self.update_log_good(&format!(
                             "The fireball does {} points of damage!",
                             hr.damage
                         ));
+                        self.beasts.entry(mon_str).dealt += hr.damage as u64;
+                        self.fight_hit(hr.damage);
                         if hr.defeated {
                             self.monster_defeated_message(hr, mon_art, mon_str);
                             done = true;
@@ -719,10 +871,16 @@ impl G {
                     Err(err) => panic!("{:#?}", err),
                 },
 
-                'D' => match self.game.spell_deathspell() {
+                Some(Action::Deathspell) => match self.game.spell_deathspell() {
                     Ok(CombatEvent::Hit(hr)) => {
                         self.update_log_good("Death... his!");
+                        self.beasts.entry(mon_str).dealt += hr.damage as u64;
+                        self.fight_hit(hr.damage);
                         self.monster_defeated_message(hr, mon_art, mon_str);
+
+                        if monster_type == MonsterType::Dragon {
+                            self.achieve(Achievement::DragonDeathspell);
+                        }
                         done = true;
                     }
                     Ok(CombatEvent::Died) => {
@@ -735,7 +893,7 @@ impl G {
                 _ => (),
             }
 
-            if ch == 'W' || ch == 'F' || ch == 'D' || ch == 'N' {
+            if action.is_some() {
                 valid = true;
             }
         }
@@ -755,19 +913,21 @@ impl G {
 
         self.update_stat(); // might not be able to bribe or cast spells anymore
 
-        match G::norm_key(getch()) {
-            'A' => done = self.combat_player_attack_melee(&mon_str, &mon_art),
-            'B' => {
+        match self.key_action(KeyContext::Combat, self.getkey()) {
+            Some(Action::Attack) => done = self.combat_player_attack_melee(&mon_str, &mon_art),
+            Some(Action::Bribe) => {
                 if self.game.bribe_possible() && self.game.player_has_any_treasure() {
                     done = self.combat_bribe();
                     *bribed = done;
                 }
             }
-            'C' => done = self.combat_player_attack_spell(&mon_str, &mon_art),
-            'N' => self.combat_retreat(Direction::North),
-            'S' => self.combat_retreat(Direction::South),
-            'W' => self.combat_retreat(Direction::West),
-            'E' => self.combat_retreat(Direction::East),
+            Some(Action::Cast) => {
+                done = self.combat_player_attack_spell(monster_type, &mon_str, &mon_art)
+            }
+            Some(Action::North) => self.combat_retreat(Direction::North),
+            Some(Action::South) => self.combat_retreat(Direction::South),
+            Some(Action::West) => self.combat_retreat(Direction::West),
+            Some(Action::East) => self.combat_retreat(Direction::East),
             _ => (),
         }
 
@@ -778,23 +938,38 @@ impl G {
     fn combat_monster_attack(&mut self, monster_type: MonsterType) {
         let mon_str = G::monster_name(monster_type);
 
+        // Armor soaks up some hits, so see what actually came off
+        let strength = self.game.player_stat(Stat::Strength);
+
         match self.game.be_attacked() {
             Ok(CombatEvent::MonsterWebbed) => {
                 self.update_log_good(&format!("The {} is stuck and can't attack!", mon_str));
             }
 
-            Ok(CombatEvent::MonsterHit(_damage, _defeated, armor_destroyed, web_broke)) => {
+            Ok(CombatEvent::MonsterHit(damage, defeated, armor_destroyed, web_broke)) => {
+                let lost = strength.saturating_sub(self.game.player_stat(Stat::Strength));
+
+                self.beasts.entry(&mon_str).taken += damage as u64;
+                self.fight_hurt(damage, lost);
+
                 if web_broke {
                     self.update_log_bad("The web just broke!");
                 }
 
                 self.update_log(&format!("The {} attacks!", mon_str));
 
-                self.update_log_bad("OUCH! He hit you!");
+                self.update_log_bad(&format!(
+                    "OUCH! He hit you for {} damage, {} of it off your strength!",
+                    damage, lost
+                ));
 
                 if armor_destroyed {
                     self.update_log_bad("Your armor is destroyed--good luck!");
                 }
+
+                if defeated {
+                    self.update_log_bad("It's a fatal blow!");
+                }
             }
 
             Ok(CombatEvent::MonsterMiss) => {
@@ -825,6 +1000,9 @@ impl G {
 
         self.update_log_bad(&format!("You're facing {}!", mon));
 
+        self.beasts.entry(&mon_str).met += 1;
+        self.fight = Some(Fight::new(monster_type));
+
         while !done {
             //self.update_log(&format!(">> {:#?}", self.game.state()));
             match self.game.state() {
@@ -843,9 +1021,16 @@ impl G {
             }
         } // while !done
 
+        self.end_fight();
         self.set_statmode(StatMode::None);
 
+        if retreated {
+            self.beasts.entry(&mon_str).retreats += 1;
+        }
+
         if bribed {
+            self.beasts.entry(&mon_str).bribes += 1;
+
             self.update_log(&format!(
                 "The {} is happy with your bribe... for now.",
                 &mon_str
@@ -862,18 +1047,19 @@ impl G {
 
     /// Main game loop
     fn run(&mut self) {
-        G::show_cursor(false);
-        cbreak();
-        noecho();
+        self.ui.show_cursor(false);
 
         self.intro();
 
+        if !self.main_menu() {
+            self.ui.show_cursor(true);
+            return;
+        }
+
         let mut playing = true;
 
-        clear();
         while playing {
-            erase();
-            refresh();
+            self.ui.clear();
 
             self.restart(true);
 
@@ -889,6 +1075,8 @@ impl G {
 
             let mut alive = true;
 
+            self.in_castle = true;
+
             self.update_log_attr(
                 "You enter the castle and begin!\n",
                 self.wcget("bold-yellow"),
@@ -917,28 +1105,46 @@ impl G {
                 self.update_map(self.options.discover_all);
                 self.update_stat();
 
-                if !automove {
-                    let key = getch();
-
-                    match G::norm_key(key) {
-                        'A' => self.attack(),
-                        'N' => self.move_dir(Direction::North),
-                        'S' => self.move_dir(Direction::South),
-                        'W' => self.move_dir(Direction::West),
-                        'E' => self.move_dir(Direction::East),
-                        'D' => self.drink_down(),
-                        'U' => self.move_stairs(Stairs::Up),
-                        'F' => self.flare(),
-                        'L' => self.lamp(),
-                        'G' => self.gaze(),
-                        'O' => self.open(),
-                        'R' => self.read(),
-                        'I' => self.show_inventory(),
-                        'T' => self.trade_teleport(),
-                        'P' => self.teleport(),
-                        'H' | '?' => self.help(),
-                        'C' => self.info(),
-                        'Q' => {
+                if automove {
+                    automove = false;
+                } else if let Some(step) = self.next_travel_step() {
+                    self.take_step(step);
+                } else {
+                    let mut key = self.getkey();
+
+                    if self.browse_dir(key).is_some() {
+                        key = self.browse_levels(key);
+                    }
+
+                    match self.key_action(KeyContext::Explore, key) {
+                        Some(Action::Attack) => self.attack(),
+                        Some(Action::North) => self.move_dir(Direction::North),
+                        Some(Action::South) => self.move_dir(Direction::South),
+                        Some(Action::West) => self.move_dir(Direction::West),
+                        Some(Action::East) => self.move_dir(Direction::East),
+                        Some(Action::Down) => self.drink_down(),
+                        Some(Action::Up) => self.move_stairs(Stairs::Up),
+                        Some(Action::Flare) => self.flare(),
+                        Some(Action::Lamp) => self.lamp(),
+                        Some(Action::Gaze) => self.gaze(),
+                        Some(Action::Open) => self.open(),
+                        Some(Action::Read) => self.read(),
+                        Some(Action::Inventory) => self.show_inventory(),
+                        Some(Action::Trade) => self.trade_teleport(),
+                        Some(Action::Teleport) => self.teleport(),
+                        Some(Action::Help) => self.help(),
+                        Some(Action::Legend) => self.legend(),
+                        Some(Action::Copyright) => self.info(),
+                        Some(Action::History) => self.history(),
+                        Some(Action::Annotate) => self.annotate(),
+                        Some(Action::Notes) => self.notes_popup(),
+                        Some(Action::Look) => self.look(),
+                        Some(Action::Rumors) => self.rumors_popup(),
+                        Some(Action::Bestiary) => self.bestiary_popup(),
+                        Some(Action::Travel) => self.travel(),
+                        Some(Action::Explore) => self.explore(),
+                        Some(Action::Run) => self.start_run(),
+                        Some(Action::Quit) => {
                             if self.verify_quit(false) {
                                 alive = false;
                                 // TODO play again?
@@ -948,8 +1154,6 @@ impl G {
                         }
                         _ => (),
                     }
-                } else {
-                    automove = false;
                 }
 
                 if self.game.state() == GameState::Dead {
@@ -970,8 +1174,13 @@ impl G {
 
                 self.set_statmode_display();
 
-                match self.game.room_effect() {
-                    Event::FoundGold(_) => {
+                let event = self.game.room_effect();
+
+                self.travel_event(&event);
+
+                match event {
+                    Event::FoundGold(n) => {
+                        self.tally.gold(n);
                         self.update_log(&format!(
                             "You found gold! You now have {} GPs.",
                             self.game.player_gp()
@@ -1002,11 +1211,15 @@ impl G {
                         automove = true;
                     }
                     Event::Treasure(t) => {
+                        self.tally.treasure();
+
                         let msg = &format!(
                             "Here you find the {}! It's now yours!",
                             G::treasure_name(*t.treasure_type())
                         );
                         self.update_log_good(msg);
+
+                        self.treasure_achievements();
                     }
                     Event::Combat(monster_type) => automove = self.combat(monster_type),
                     Event::Vendor => {
@@ -1016,48 +1229,103 @@ impl G {
                 }
             } // while alive
 
+            self.in_castle = false;
+
+            let ending = match self.game.state() {
+                GameState::Dead => Ending::Died,
+                GameState::Exit if self.game.player_has_orb_of_zot() => Ending::Won,
+                GameState::Exit => Ending::Escaped,
+                _ => Ending::Quit,
+            };
+
+            let scores = self.record_score(ending);
+            self.record_career(ending);
+            self.ending_achievements(ending);
+            self.record_bestiary();
+
             if playing {
-                playing = self.game_summary();
+                playing = self.game_summary(scores);
             }
         } // while playing
 
-        nocbreak();
-        echo();
-        G::show_cursor(true);
+        self.ui.show_cursor(true);
     }
 
     /// Redraw the main screen windows
     fn redraw_underwins(&self) {
-        redrawwin(self.logwin);
-        wrefresh(self.logwin);
-        redrawwin(self.statwin);
-        wrefresh(self.statwin);
-        redrawwin(self.mapwin);
-        wrefresh(self.mapwin);
+        self.ui.touch(self.logwin);
+        self.ui.refresh(self.logwin);
+        self.ui.touch(self.statwin);
+        self.ui.refresh(self.statwin);
+        self.ui.touch(self.mapwin);
+        self.ui.refresh(self.mapwin);
+
+        if let Some(w) = self.atlaswin.get() {
+            self.ui.touch(w);
+            self.ui.refresh(w);
+        }
     }
 }
 
 /// Gather command line options
 fn gather_options() -> Opts {
     let all_args: Vec<String> = env::args().collect();
-    let args = &all_args[1..];
+    let mut args = all_args[1..].iter();
 
     let mut discover_all = false;
     let mut force_bw = false;
     let mut give_orb_of_zot = false;
     let mut give_runestaff = false;
     let mut locations = false;
+    let mut movement = None;
+    let mut size = None;
+    let mut scores = None;
+    let mut profile = None;
+    let mut data_dir = None;
+
+    while let Some(a) = args.next() {
+        match a.as_ref() {
+            "--movement" | "-m" => {
+                let name = args.next().expect("missing movement scheme");
+
+                match Movement::from_name(name) {
+                    Some(m) => movement = Some(m),
+                    None => panic!("unknown movement scheme: {}", name),
+                }
+            }
+            "--size" => {
+                let s = args.next().expect("missing castle size");
+
+                match G::parse_size(s) {
+                    Some(v) => size = Some(v),
+                    None => panic!(
+                        "invalid castle size: {} (sides must be {} to {}, like 12x12x10)",
+                        s, MIN_SIDE, MAX_SIDE
+                    ),
+                }
+            }
+            "--scores" => scores = Some(args.next().expect("missing score file").clone()),
+            "--profile" => {
+                let name = args.next().expect("missing profile name");
+                let clean = G::clean_profile_name(name);
+
+                if clean.is_empty() || clean != *name {
+                    panic!(
+                        "invalid profile name: {} (use letters, digits, - and _)",
+                        name
+                    );
+                }
 
-    if cfg!(feature = "cheat") {
-        for a in args {
-            match a.as_ref() {
-                "-d" => discover_all = true,
-                "-b" => force_bw = true,
-                "-z" => give_orb_of_zot = true,
-                "-r" => give_runestaff = true,
-                "-l" => locations = true,
-                any => panic!("unknown command arg: {}", any),
+                profile = Some(clean);
             }
+            "--data-dir" => data_dir = Some(args.next().expect("missing data directory").clone()),
+            "-d" if cfg!(feature = "cheat") => discover_all = true,
+            "-b" if cfg!(feature = "cheat") => force_bw = true,
+            "-z" if cfg!(feature = "cheat") => give_orb_of_zot = true,
+            "-r" if cfg!(feature = "cheat") => give_runestaff = true,
+            "-l" if cfg!(feature = "cheat") => locations = true,
+            "-d" | "-b" | "-z" | "-r" | "-l" => (), // cheats are off in this build
+            any => panic!("unknown command arg: {}", any),
         }
     }
 
@@ -1067,6 +1335,11 @@ fn gather_options() -> Opts {
         give_orb_of_zot,
         give_runestaff,
         locations,
+        movement,
+        size,
+        scores,
+        profile,
+        data_dir,
     }
 }
 
@@ -1074,21 +1347,27 @@ fn gather_options() -> Opts {
 fn main() {
     let options = gather_options();
 
-    initscr();
-
-    if !options.force_bw && has_colors() {
-        start_color();
-    }
+    let mut keys = match Keymap::load() {
+        Ok(k) => k,
+        Err(err) => {
+            eprintln!("wizcasn: {}", err);
+            std::process::exit(1);
+        }
+    };
 
-    keypad(stdscr(), true);
+    if let Some(m) = options.movement {
+        keys.movement = m;
 
-    set_escdelay(100);
+        // The keymap file might have bindings these keys would hide
+        if let Err(err) = keys.validate() {
+            eprintln!("wizcasn: {}", err);
+            std::process::exit(1);
+        }
+    }
 
-    refresh(); // If we don't do this first, windows don't show up
+    let ui = Box::new(Curses::new(!options.force_bw));
 
-    let mut g = G::new(options);
+    let mut g = G::new(ui, options, keys);
 
     g.run();
-
-    endwin();
 }
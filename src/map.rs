@@ -1,63 +1,254 @@
 use crate::G;
-use ncurses::*;
+use crate::frontend::*;
+use crate::keymap::{Action, KeyContext};
+use crate::knowledge::Sighting;
+use crate::notes::Note;
+use crate::win::{LOG_Y, MAP_COLS};
+
+use wizardscastle::room::Room;
+
+/// The part of a level that fits in a window, and how it's spaced out
+struct MapView {
+    /// Columns and lines per room
+    cell_w: i32,
+    cell_h: i32,
+
+    /// The first room shown
+    x0: u32,
+    y0: u32,
+
+    /// How many rooms are shown across and down
+    xn: u32,
+    yn: u32,
+}
 
 impl G {
+    /// Work out how to show a level in a window, keeping the player (or the
+    /// map cursor) in view
+    ///
+    /// Castles up to 8x8 get the roomy original layout. Bigger ones are
+    /// packed tighter, and if they still don't fit, the view scrolls.
+    fn map_view(&self, lines: i32, cols: i32) -> MapView {
+        let xs = self.game.dungeon_xsize();
+        let ys = self.game.dungeon_ysize();
+
+        // Rooms are 3 wide, with 3 columns and a line between them
+        let roomy = 6 * xs as i32 - 3 <= cols - 2 && 2 * ys as i32 - 1 <= lines - 2;
+
+        let (cell_w, cell_h) = if roomy { (6, 2) } else { (3, 1) };
+
+        let xn = (((cols - 2 + cell_w - 3) / cell_w).max(1) as u32).min(xs);
+        let yn = (((lines - 2 + cell_h - 1) / cell_h).max(1) as u32).min(ys);
+
+        let (cx, cy) = self
+            .map_cursor
+            .map_or((self.game.player_x(), self.game.player_y()), |c| (c.x, c.y));
+
+        let x0 = cx.saturating_sub(xn / 2).min(xs - xn);
+        let y0 = cy.saturating_sub(yn / 2).min(ys - yn);
+
+        MapView {
+            cell_w,
+            cell_h,
+            x0,
+            y0,
+            xn,
+            yn,
+        }
+    }
+
+    /// Draw one room of the map at the cursor
+    ///
+    /// The player's room is bracketed, rooms with notes have the note's mark
+    /// on the right, and the map cursor, if any, is in reverse video. Rooms
+    /// the player hasn't seen show what an orb or lamp last showed there, if
+    /// anything, in a color of their own.
+    fn draw_room(&self, w: Win, x: u32, y: u32, z: u32, show_all: bool) {
+        let r = self.game.dungeon_room_at(x, y, z);
+
+        let bracket =
+            x == self.game.player_x() && y == self.game.player_y() && z == self.game.player_z();
+
+        let cursor = self.map_cursor.is_some_and(|c| (c.x, c.y) == (x, y));
+
+        if cursor {
+            self.ui.attr_on(w, A_REVERSE);
+        }
+
+        self.draw_room_inner(
+            w,
+            r,
+            bracket,
+            self.note_at(x, y, z),
+            self.rumor_at(x, y, z),
+            show_all,
+        );
+
+        if cursor {
+            self.ui.attr_off(w, A_REVERSE);
+        }
+    }
+
+    fn draw_room_inner(
+        &self,
+        w: Win,
+        r: &Room,
+        bracket: bool,
+        note: Option<&Note>,
+        rumor: Option<&Sighting>,
+        show_all: bool,
+    ) {
+        if self.game.player_is_blind() {
+            self.ui.addstr(w, " - ");
+            return;
+        }
+
+        if bracket {
+            self.ui.attr_on(w, A_BOLD);
+            self.ui.addstr(w, "<");
+            self.ui.attr_off(w, A_BOLD);
+        } else {
+            self.ui.addstr(w, " ");
+        }
+
+        if r.discovered || show_all {
+            let room_ch = G::room_char(&r.roomtype);
+            let attr_str = format!("room-{}", room_ch);
+
+            self.wcon(w, &attr_str);
+            self.ui.addstr(w, &format!("{}", room_ch));
+            self.wcoff(w, &attr_str);
+        } else if let Some(s) = rumor {
+            self.wcon(w, "rumor");
+            self.ui.addch(w, s.glyph());
+            self.wcoff(w, "rumor");
+        } else {
+            self.ui.attr_on(w, A_DIM);
+            self.ui.addstr(w, "?");
+            self.ui.attr_off(w, A_DIM);
+        }
+
+        if bracket {
+            self.ui.attr_on(w, A_BOLD);
+            self.ui.addstr(w, ">");
+            self.ui.attr_off(w, A_BOLD);
+        } else if let Some(n) = note {
+            self.wcon(w, "note");
+            self.ui.addch(w, n.mark);
+            self.wcoff(w, "note");
+        } else {
+            self.ui.addstr(w, " ");
+        }
+    }
+
     pub fn update_map(&self, show_all: bool) {
-        let z = self.game.player_z();
-
-        werase(self.mapwin);
-
-        let _ = waddstr(self.mapwin, "\n ");
-
-        for y in 0..self.game.dungeon_ysize() {
-            for x in 0..self.game.dungeon_xsize() {
-                if x >= 1 {
-                    let _ = waddstr(self.mapwin, "   ");
-                }
-
-                let r = self.game.dungeon_room_at(x, y, z);
-
-                let bracket = x == self.game.player_x() && y == self.game.player_y();
-
-                if self.game.player_is_blind() {
-                    let _ = waddstr(self.mapwin, " - ");
-                } else {
-                    if bracket {
-                        wattr_on(self.mapwin, A_BOLD);
-                        let _ = waddstr(self.mapwin, "<");
-                        wattr_off(self.mapwin, A_BOLD);
-                    } else {
-                        let _ = waddstr(self.mapwin, " ");
-                    }
-
-                    if r.discovered || show_all {
-                        let room_ch = G::room_char(&r.roomtype);
-                        let attr_str = format!("room-{}", room_ch);
-
-                        self.wcon(self.mapwin, &attr_str);
-                        let _ = waddstr(self.mapwin, &format!("{}", room_ch));
-                        self.wcoff(self.mapwin, &attr_str);
-                    } else {
-                        wattr_on(self.mapwin, A_DIM);
-                        let _ = waddstr(self.mapwin, "?");
-                        wattr_off(self.mapwin, A_DIM);
-                    }
-
-                    if bracket {
-                        wattr_on(self.mapwin, A_BOLD);
-                        let _ = waddstr(self.mapwin, ">");
-                        wattr_off(self.mapwin, A_BOLD);
-                    } else {
-                        let _ = waddstr(self.mapwin, " ");
-                    }
-                }
+        let z = self.map_z.unwrap_or(self.game.player_z());
+
+        let v = self.map_view(LOG_Y, MAP_COLS);
+
+        self.ui.erase(self.mapwin);
+
+        for y in v.y0..v.y0 + v.yn {
+            for x in v.x0..v.x0 + v.xn {
+                let row = 1 + (y - v.y0) as i32 * v.cell_h;
+                let col = 1 + (x - v.x0) as i32 * v.cell_w;
+
+                self.ui.mv(self.mapwin, row, col);
+                self.draw_room(self.mapwin, x, y, z, show_all);
             }
+        }
+
+        self.ui.border(self.mapwin);
+
+        if let Some(c) = self.map_cursor {
+            let hint = if c.picking {
+                "ENTER picks, ESC cancels"
+            } else {
+                "looking"
+            };
+
+            self.ui.attr_on(self.mapwin, A_BOLD);
+            self.ui
+                .mvaddstr(self.mapwin, 0, 2, &format!(" Level {} - {} ", z + 1, hint));
+            self.ui.attr_off(self.mapwin, A_BOLD);
+        } else if let Some(mz) = self.map_z {
+            let here = if mz == self.game.player_z() {
+                String::from("you're here")
+            } else {
+                format!("you're on {}", self.game.player_z() + 1)
+            };
+
+            self.ui.attr_on(self.mapwin, A_BOLD);
+            self.ui.mvaddstr(
+                self.mapwin,
+                0,
+                2,
+                &format!(
+                    " Level {} of {} - {} ",
+                    mz + 1,
+                    self.game.dungeon_zsize(),
+                    here
+                ),
+            );
+            self.ui.attr_off(self.mapwin, A_BOLD);
+        }
+
+        // Say which part of the level is showing if it doesn't all fit
+        if v.xn < self.game.dungeon_xsize() || v.yn < self.game.dungeon_ysize() {
+            self.ui.mvaddstr(
+                self.mapwin,
+                LOG_Y - 1,
+                2,
+                &format!(
+                    " x {}-{}  y {}-{} ",
+                    v.x0 + 1,
+                    v.x0 + v.xn,
+                    v.y0 + 1,
+                    v.y0 + v.yn
+                ),
+            );
+        }
+
+        self.ui.refresh(self.mapwin);
+
+        self.update_atlas(show_all);
+    }
+
+    /// Tell which way a key moves the map between levels, if it does
+    pub fn browse_dir(&self, key: i32) -> Option<i32> {
+        match key {
+            KEY_PPAGE => Some(-1),
+            KEY_NPAGE => Some(1),
+            _ => match self.key_action(KeyContext::Explore, key) {
+                Some(Action::LevelUp) => Some(-1),
+                Some(Action::LevelDown) => Some(1),
+                _ => None,
+            },
+        }
+    }
+
+    /// Show other levels of the map, starting with `key`, until a key that
+    /// isn't for browsing
+    ///
+    /// Browsing doesn't take a turn. The map goes back to the player's level
+    /// and the other key is returned to be handled as usual.
+    pub fn browse_levels(&mut self, key: i32) -> i32 {
+        let mut key = key;
+
+        while let Some(dir) = self.browse_dir(key) {
+            let z = self.map_z.unwrap_or(self.game.player_z()) as i32 + dir;
 
-            let _ = waddstr(self.mapwin, "\n\n ");
+            self.map_z = Some(z.clamp(0, self.game.dungeon_zsize() as i32 - 1) as u32);
+
+            self.update_map(self.options.discover_all);
+
+            key = self.getkey();
         }
 
-        box_(self.mapwin, 0, 0);
+        self.map_z = None;
+
+        self.update_map(self.options.discover_all);
 
-        wrefresh(self.mapwin);
+        key
     }
 }
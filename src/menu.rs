@@ -0,0 +1,41 @@
+//! The main menu, shown once before the first game
+use crate::G;
+
+impl G {
+    /// Let the player start a game, look at the high scores or
+    /// achievements, or leave
+    ///
+    /// Returns false if they'd rather leave.
+    pub fn main_menu(&mut self) -> bool {
+        loop {
+            let w = self.popup(11, 40);
+
+            self.wcon(w, G::A_TITLE());
+            self.mvwprintw_center(w, 2, "What'll it be?");
+            self.wcoff(w, G::A_TITLE());
+
+            self.ui.mv(w, 4, 0);
+
+            self.wprintw_center_notrim(w, "|[N]|ew game           \n");
+            self.wprintw_center_notrim(w, "|[H]|all of Fame       \n");
+            self.wprintw_center_notrim(w, "|[A]|chievements       \n");
+            self.wprintw_center_notrim(w, "|[Q]|uit               \n");
+
+            self.ui.border(w);
+            self.ui.refresh(w);
+
+            let ch = G::norm_key(self.getkey());
+
+            self.popup_close(w);
+            self.redraw_underwins();
+
+            match ch {
+                'N' => return true,
+                'H' => self.hall_of_fame(&self.load_scores(), None),
+                'A' => self.achievements_screen(),
+                'Q' => return false,
+                _ => (),
+            }
+        }
+    }
+}
@@ -0,0 +1,128 @@
+//! The player's notes on rooms
+//!
+//! Notes are kept apart from the castle, so they stay put even if a curse
+//! makes the player forget the map.
+use crate::G;
+use crate::frontend::*;
+use crate::keymap::{Action, KeyContext};
+
+/// The mark a note gets if the player doesn't pick one
+const DEFAULT_MARK: char = '*';
+
+/// A note on a room
+#[derive(Debug, Clone)]
+pub struct Note {
+    pub mark: char,
+    pub text: String,
+}
+
+impl G {
+    /// The note on a room, if there is one
+    pub fn note_at(&self, x: u32, y: u32, z: u32) -> Option<&Note> {
+        self.notes.get(&(z, y, x))
+    }
+
+    /// Write, change, or remove the note on a room
+    pub fn annotate(&mut self) {
+        let Some((x, y, z)) = self.pick_room(false) else {
+            return;
+        };
+
+        let old = self.note_at(x, y, z).cloned();
+
+        let w = self.popup(12, 56);
+
+        self.wcon(w, G::A_TITLE());
+        self.mvwprintw_center(
+            w,
+            2,
+            &format!("Note on the room at ({},{}) level {}", x + 1, y + 1, z + 1),
+        );
+        self.wcoff(w, G::A_TITLE());
+
+        // Not centered with mvwprintw_center(), which would take any | or %
+        // in the note as formatting
+        if let Some(n) = &old {
+            self.ui
+                .mvaddstr(w, 4, 4, &format!("{}  {}", n.mark, n.text));
+        }
+
+        self.mvwprintw_center(w, 6, "Type a symbol to mark the room with,");
+        self.mvwprintw_center(
+            w,
+            7,
+            &format!(
+                "|[ENTER]| for {}, or |[SPACE]| to remove the note",
+                DEFAULT_MARK
+            ),
+        );
+
+        self.ui.border(w);
+        self.ui.refresh(w);
+
+        let mark = loop {
+            match self.getkey() {
+                10 | 13 | KEY_ENTER => break Some(old.as_ref().map_or(DEFAULT_MARK, |n| n.mark)),
+                32 => break None,
+                k if (33..127).contains(&k) => break Some(k as u8 as char),
+                _ => (),
+            }
+        };
+
+        if let Some(mark) = mark {
+            self.ui.erase(w);
+
+            self.wcon(w, G::A_TITLE());
+            self.mvwprintw_center(w, 2, "What's the note?");
+            self.wcoff(w, G::A_TITLE());
+
+            self.ui.mvaddstr(w, 4, 4, &format!("{}  ", mark));
+
+            self.mvwprintw_center(w, 6, "|[ENTER]| by itself for just the mark");
+
+            self.ui.border(w);
+
+            self.ui.mv(w, 4, 7);
+            self.ui.show_cursor(true);
+            let text = self.read_line(w, 45).trim().to_string();
+            self.ui.show_cursor(false);
+
+            self.notes.insert((z, y, x), Note { mark, text });
+        } else {
+            self.notes.remove(&(z, y, x));
+        }
+
+        self.popup_close(w);
+
+        self.redraw_underwins();
+        self.update_map(self.options.discover_all);
+    }
+
+    /// Show all the notes
+    pub fn notes_popup(&self) {
+        let rows: Vec<String> = self
+            .notes
+            .iter()
+            .map(|(&(z, y, x), n)| {
+                format!(
+                    "Level {:<2} ({:>2},{:>2})  {}  {}",
+                    z + 1,
+                    x + 1,
+                    y + 1,
+                    n.mark,
+                    n.text
+                )
+            })
+            .collect();
+
+        self.list_popup(
+            "Your notes",
+            &format!(
+                "No notes yet. {} a room to add one.",
+                self.keys
+                    .menuitem(KeyContext::Explore, Action::Annotate, "Annotate")
+            ),
+            &rows,
+        );
+    }
+}
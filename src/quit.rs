@@ -1,5 +1,5 @@
 use crate::G;
-use ncurses::*;
+use crate::frontend::*;
 
 impl G {
     /// Verify the player wants to quit
@@ -14,22 +14,22 @@ impl G {
 
         let width = s.len() + 10;
 
-        let w = G::popup(7, width as i32);
+        let w = self.popup(7, width as i32);
 
         self.wcon(w, "bold-yellow");
         self.mvwprintw_center(w, 2, s);
         self.wcoff(w, "bold-yellow");
 
-        wattr_on(w, A_BOLD);
+        self.ui.attr_on(w, A_BOLD);
         self.mvwprintw_center(w, 4, "|[Y]|es or |[N]|o");
-        wattr_off(w, A_BOLD);
+        self.ui.attr_off(w, A_BOLD);
 
-        box_(w, 0, 0);
-        wrefresh(w);
+        self.ui.border(w);
+        self.ui.refresh(w);
 
-        let key = getch();
+        let key = self.getkey();
 
-        G::popup_close(w);
+        self.popup_close(w);
 
         G::norm_key(key) == 'Y'
     }
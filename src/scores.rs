@@ -0,0 +1,369 @@
+//! High scores
+//!
+//! Scores are kept in a text file that's locked while it's read or
+//! written, so everyone on a machine can share one, the way the BSD games
+//! did. The file is the one given with `--scores`, or else
+//! `/var/games/wizcasn.scores` if it's there and the player can write to
+//! it, or else one in the player's own data directory.
+//!
+//!    wizcasn-scores 1
+//!    score 7150 won 312 1 Hobbit 1760000000 beej
+//!
+//! Each score line has the points, how the game ended, the turn, the level
+//! the player was on, their race, when it was (in seconds since 1970), and
+//! who played.
+use crate::G;
+use crate::frontend::*;
+
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const MAGIC: &str = "wizcasn-scores 1";
+
+/// The shared score file, if the system has one
+const SYSTEM_SCORES: &str = "/var/games/wizcasn.scores";
+
+/// How many scores the file keeps, and how many the hall of fame shows
+const KEPT: usize = 100;
+const SHOWN: usize = 10;
+
+/// Points for what the player has at the end
+const ORB_POINTS: u32 = 5000;
+const RUNESTAFF_POINTS: u32 = 1000;
+const TREASURE_POINTS: u32 = 500;
+const ESCAPE_POINTS: u32 = 1000;
+
+/// A win gets this many points for each turn it came in under `FAST_TURNS`
+const FAST_POINTS: u32 = 5;
+const FAST_TURNS: u32 = 1000;
+
+/// How a game ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ending {
+    Won,
+    Escaped,
+    Died,
+    Quit,
+}
+
+/// A line in the high score table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Score {
+    pub points: u32,
+    pub ending: Ending,
+    pub turn: u32,
+    pub level: u32,
+    pub race: String,
+    pub date: u64,
+    pub who: String,
+}
+
+impl Ending {
+    fn name(self) -> &'static str {
+        match self {
+            Ending::Won => "won",
+            Ending::Escaped => "escaped",
+            Ending::Died => "died",
+            Ending::Quit => "quit",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Ending> {
+        [Ending::Won, Ending::Escaped, Ending::Died, Ending::Quit]
+            .into_iter()
+            .find(|e| e.name() == name)
+    }
+}
+
+impl Score {
+    fn parse(line: &str) -> Option<Score> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+
+        match words.as_slice() {
+            ["score", points, ending, turn, level, race, date, who @ ..] => Some(Score {
+                points: points.parse().ok()?,
+                ending: Ending::from_name(ending)?,
+                turn: turn.parse().ok()?,
+                level: level.parse().ok()?,
+                race: race.to_string(),
+                date: date.parse().ok()?,
+                who: who.join(" "),
+            }),
+            _ => None,
+        }
+    }
+
+    /// How the game ended, in words
+    fn ending_text(&self) -> String {
+        match self.ending {
+            Ending::Won => String::from("won!"),
+            Ending::Escaped => String::from("escaped"),
+            Ending::Died => format!("died on level {}", self.level),
+            Ending::Quit => format!("quit on level {}", self.level),
+        }
+    }
+}
+
+/// Turn seconds since 1970 into a year, month, and day
+fn civil_date(secs: u64) -> (i64, u32, u32) {
+    // From Howard Hinnant's days_from_civil, run backwards
+    let z = (secs / 86400) as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let y = yoe + era * 400 + if m <= 2 { 1 } else { 0 };
+
+    (y, m, d)
+}
+
+/// Read the scores from a locked file
+fn read_scores(f: &mut File) -> io::Result<Vec<Score>> {
+    let mut text = String::new();
+
+    f.read_to_string(&mut text)?;
+
+    Ok(text.lines().filter_map(Score::parse).collect())
+}
+
+impl G {
+    /// Return the directory the game keeps the player's files in
+    pub fn data_dir(&self) -> Option<PathBuf> {
+        if let Some(dir) = &self.options.data_dir {
+            return Some(PathBuf::from(dir));
+        }
+
+        if let Ok(dir) = env::var("XDG_DATA_HOME")
+            && !dir.is_empty()
+        {
+            return Some(PathBuf::from(dir).join("wizcasn"));
+        }
+
+        env::var("HOME")
+            .ok()
+            .map(|home| PathBuf::from(home).join(".local/share/wizcasn"))
+    }
+
+    /// Where the high scores are kept
+    fn score_path(&self) -> Option<PathBuf> {
+        if let Some(path) = &self.options.scores {
+            return Some(PathBuf::from(path));
+        }
+
+        if OpenOptions::new().append(true).open(SYSTEM_SCORES).is_ok() {
+            return Some(PathBuf::from(SYSTEM_SCORES));
+        }
+
+        self.data_dir().map(|d| d.join("scores"))
+    }
+
+    /// Score the game that just ended
+    ///
+    /// The Orb of Zot, the Runestaff, treasures, and gold all count, and so
+    /// does getting out alive. A win is worth more the fewer turns it took.
+    /// Dying or quitting halves it all.
+    pub fn score(&self, ending: Ending) -> u32 {
+        let mut points =
+            self.game.player_gp() + self.game.player_get_treasures().len() as u32 * TREASURE_POINTS;
+
+        if self.game.player_has_runestaff() {
+            points += RUNESTAFF_POINTS;
+        }
+
+        if self.game.player_has_orb_of_zot() {
+            points += ORB_POINTS;
+        }
+
+        match ending {
+            Ending::Won => {
+                points += ESCAPE_POINTS + FAST_TURNS.saturating_sub(*self.game.turn()) * FAST_POINTS
+            }
+            Ending::Escaped => points += ESCAPE_POINTS,
+            Ending::Died | Ending::Quit => points /= 2,
+        }
+
+        points
+    }
+
+    /// Tell if the game that just ended counts: cheats weren't on
+    pub fn game_counts(&self) -> bool {
+        let o = &self.options;
+
+        !(o.discover_all || o.give_orb_of_zot || o.give_runestaff || o.locations)
+    }
+
+    /// Add the game that just ended to the high scores
+    ///
+    /// Returns all the scores and where the new one is among them, or
+    /// `None` if the game doesn't count or the score file couldn't be
+    /// written.
+    pub fn record_score(&self, ending: Ending) -> Option<(Vec<Score>, Option<usize>)> {
+        if !self.game_counts() {
+            return None;
+        }
+
+        let score = Score {
+            points: self.score(ending),
+            ending,
+            turn: *self.game.turn(),
+            level: self.game.player_z() + 1,
+            race: self.race_name().to_string(),
+            date: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs()),
+            who: env::var("USER")
+                .or_else(|_| env::var("LOGNAME"))
+                .unwrap_or_else(|_| String::from("someone")),
+        };
+
+        let path = self.score_path()?;
+
+        let result = (|| {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+
+            let mut f = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(false)
+                .open(&path)?;
+
+            f.lock()?;
+
+            let mut scores = read_scores(&mut f)?;
+
+            // After any it ties with, since they got there first
+            let i = scores
+                .iter()
+                .position(|s| s.points < score.points)
+                .unwrap_or(scores.len());
+
+            scores.insert(i, score);
+            scores.truncate(KEPT);
+
+            let mut text = format!("{}\n", MAGIC);
+
+            for s in &scores {
+                text.push_str(&format!(
+                    "score {} {} {} {} {} {} {}\n",
+                    s.points,
+                    s.ending.name(),
+                    s.turn,
+                    s.level,
+                    s.race,
+                    s.date,
+                    s.who
+                ));
+            }
+
+            f.seek(SeekFrom::Start(0))?;
+            f.set_len(0)?;
+            f.write_all(text.as_bytes())?;
+
+            // The lock goes when the file closes
+            Ok::<_, io::Error>((scores, (i < KEPT).then_some(i)))
+        })();
+
+        match result {
+            Ok(r) => Some(r),
+            Err(err) => {
+                self.popup_error(&format!(
+                    "Couldn't save your score in {}: {}",
+                    path.display(),
+                    err
+                ));
+                None
+            }
+        }
+    }
+
+    /// Read the high scores, best first
+    pub fn load_scores(&self) -> Vec<Score> {
+        let Some(path) = self.score_path() else {
+            return Vec::new();
+        };
+
+        let Ok(mut f) = File::open(path) else {
+            return Vec::new();
+        };
+
+        if f.lock_shared().is_err() {
+            return Vec::new();
+        }
+
+        read_scores(&mut f).unwrap_or_default()
+    }
+
+    /// Show the best scores, with the new one, if any, highlighted
+    pub fn hall_of_fame(&self, scores: &[Score], new: Option<usize>) {
+        // The new score gets its own line at the bottom if it's not near
+        // the top
+        let extra = new.is_some_and(|i| i >= SHOWN);
+        let rows = scores.len().clamp(1, SHOWN) as i32 + if extra { 2 } else { 0 };
+
+        let w = self.popup(rows + 8, 72);
+
+        self.wcon(w, G::A_TITLE());
+        self.mvwprintw_center(w, 2, "Hall of Fame");
+        self.wcoff(w, G::A_TITLE());
+
+        if scores.is_empty() {
+            self.mvwprintw_center(w, 4, "No one has played yet. Be the first!");
+        }
+
+        let line = |i: usize, s: &Score| {
+            let (y, m, d) = civil_date(s.date);
+
+            format!(
+                "{:>3}. {:>6}  {:<10.10} {:<6} {:<16} T{:<5} {}-{:02}-{:02}",
+                i + 1,
+                s.points,
+                s.who,
+                s.race,
+                s.ending_text(),
+                s.turn,
+                y,
+                m,
+                d
+            )
+        };
+
+        let mut shown: Vec<(usize, i32)> = (0..scores.len().min(SHOWN))
+            .map(|i| (i, 4 + i as i32))
+            .collect();
+
+        if let Some(i) = new.filter(|_| extra) {
+            shown.push((i, 4 + SHOWN as i32 + 1));
+        }
+
+        for (i, y) in shown {
+            let attr = if Some(i) == new { A_REVERSE } else { A_NORMAL };
+
+            // Names come from the file, so they're printed as is
+            self.ui.attr_on(w, attr);
+            self.ui.mvaddstr(w, y, 2, &line(i, &scores[i]));
+            self.ui.attr_off(w, attr);
+        }
+
+        self.ui.attr_on(w, A_REVERSE);
+        self.mvwprintw_center_notrim(w, rows + 5, " Press any key ");
+        self.ui.attr_off(w, A_REVERSE);
+
+        self.ui.border(w);
+        self.ui.refresh(w);
+
+        self.getkey();
+
+        self.popup_close(w);
+
+        self.redraw_underwins();
+    }
+}
@@ -0,0 +1,109 @@
+//! Castle size
+use crate::G;
+
+/// Width, height, and depth of the castle, in rooms
+pub type Size = (u32, u32, u32);
+
+/// The size of the original castle
+pub const DEFAULT_SIZE: Size = (8, 8, 8);
+
+/// Smallest and largest any side of the castle can be
+pub const MIN_SIDE: u32 = 8;
+pub const MAX_SIDE: u32 = 30;
+
+/// Sizes offered when starting a game
+const PRESETS: [(char, &str, Size); 3] = [
+    ('S', "|[S]|tandard", DEFAULT_SIZE),
+    ('L', "|[L]|arge", (12, 12, 12)),
+    ('H', "|[H]|uge", (20, 20, 20)),
+];
+
+impl G {
+    /// The size of the current castle
+    pub fn castle_size(&self) -> Size {
+        (
+            self.game.dungeon_xsize(),
+            self.game.dungeon_ysize(),
+            self.game.dungeon_zsize(),
+        )
+    }
+
+    /// Parse a size like "12x12x10"
+    pub fn parse_size(s: &str) -> Option<Size> {
+        let sides: Vec<u32> = s
+            .split(['x', 'X'])
+            .map(|n| n.trim().parse::<u32>().ok())
+            .collect::<Option<_>>()?;
+
+        match sides.as_slice() {
+            &[x, y, z] if [x, y, z].iter().all(|n| (MIN_SIDE..=MAX_SIDE).contains(n)) => {
+                Some((x, y, z))
+            }
+            _ => None,
+        }
+    }
+
+    /// Choose the size of the castle
+    pub fn choose_size(&self) -> Size {
+        let w = self.popup(11, 50);
+
+        let size = loop {
+            self.ui.erase(w);
+
+            self.wcon(w, G::A_TITLE());
+            self.mvwprintw_center(w, 2, "How big a castle dare you enter?");
+            self.wcoff(w, G::A_TITLE());
+
+            self.ui.mv(w, 4, 0);
+
+            for (_, name, (x, y, z)) in PRESETS.iter() {
+                self.wprintw_center_notrim(
+                    w,
+                    &format!("{:<14} {:>2}x{:>2}x{:>2}\n", name, x, y, z),
+                );
+            }
+
+            self.mvwprintw_center(w, 8, "or |[C]|ustom");
+
+            self.ui.border(w);
+            self.ui.refresh(w);
+
+            let ch = G::norm_key(self.getkey());
+
+            if let Some((_, _, size)) = PRESETS.iter().find(|(k, _, _)| *k == ch) {
+                break *size;
+            }
+
+            if ch != 'C' {
+                continue;
+            }
+
+            self.ui.erase(w);
+
+            self.wcon(w, G::A_TITLE());
+            self.mvwprintw_center(w, 2, "Width x height x depth?");
+            self.wcoff(w, G::A_TITLE());
+
+            self.mvwprintw_center(
+                w,
+                6,
+                &format!("Each from {} to {}, like 12x12x10", MIN_SIDE, MAX_SIDE),
+            );
+
+            self.ui.border(w);
+
+            self.ui.mv(w, 4, 17);
+            self.ui.show_cursor(true);
+            let text = self.read_line(w, 14);
+            self.ui.show_cursor(false);
+
+            if let Some(size) = G::parse_size(&text) {
+                break size;
+            }
+        };
+
+        self.popup_close(w);
+
+        size
+    }
+}
@@ -1,5 +1,5 @@
 use crate::G;
-use ncurses::*;
+use crate::keymap::{Action, KeyContext};
 
 use wizardscastle::monster::MonsterType;
 use wizardscastle::player::Stat;
@@ -28,7 +28,7 @@ impl G {
     }
 
     pub fn update_stat(&self) {
-        werase(self.statwin);
+        self.ui.erase(self.statwin);
         let player_has_runestaff = self.game.player_has_runestaff();
         let player_has_orb_of_zot = self.game.player_has_orb_of_zot();
         let player_has_magic_item = player_has_runestaff || player_has_orb_of_zot;
@@ -129,55 +129,133 @@ impl G {
         // Additional status info
         self.update_stat_additional();
 
-        box_(self.statwin, 0, 0);
+        self.ui.border(self.statwin);
 
-        wrefresh(self.statwin);
+        self.ui.refresh(self.statwin);
     }
 
     /// Update additional status info
     fn update_stat_additional(&self) {
+        let k = &self.keys;
+
         match self.statmode {
             StatMode::None => (),
             StatMode::Lamp => {
+                let ctx = KeyContext::Lamp;
+
                 self.mvwprintw_center(self.statwin, 10, "Shine lamp which way?");
-                self.mvwprintw_center(self.statwin, 12, "|[N]|");
-                self.mvwprintw_center(self.statwin, 13, "|[W]|   |[E]|");
-                self.mvwprintw_center(self.statwin, 14, "|[S]|");
+                self.mvwprintw_center(self.statwin, 12, &k.keyitem(ctx, Action::North));
+                self.mvwprintw_center(
+                    self.statwin,
+                    13,
+                    &format!(
+                        "{}   {}",
+                        k.keyitem(ctx, Action::West),
+                        k.keyitem(ctx, Action::East)
+                    ),
+                );
+                self.mvwprintw_center(self.statwin, 14, &k.keyitem(ctx, Action::South));
             }
             StatMode::Vendor => {
-                self.mvwprintw_center(self.statwin, 10, "|[T]|rade\n");
-                self.wprintw_center(self.statwin, "|[A]|ttack");
+                let ctx = KeyContext::Explore;
+
+                self.mvwprintw_center(
+                    self.statwin,
+                    10,
+                    &format!("{}\n", k.menuitem(ctx, Action::Trade, "Trade")),
+                );
+                self.wprintw_center(self.statwin, &k.menuitem(ctx, Action::Attack, "Attack"));
             }
             StatMode::Combat => {
-                self.mvwprintw_center(self.statwin, 10, "|[A]|ttack\n");
+                let ctx = KeyContext::Combat;
+
+                self.mvwprintw_center(
+                    self.statwin,
+                    10,
+                    &format!("{}\n", k.menuitem(ctx, Action::Attack, "Attack")),
+                );
                 if self.game.bribe_possible() && self.game.player_has_any_treasure() {
-                    self.wprintw_center(self.statwin, "|[B]|ribe\n");
+                    self.wprintw_center(
+                        self.statwin,
+                        &format!("{}\n", k.menuitem(ctx, Action::Bribe, "Bribe")),
+                    );
                 }
                 if self.game.spell_possible() {
-                    self.wprintw_center(self.statwin, "|[C]|ast spell\n");
+                    self.wprintw_center(
+                        self.statwin,
+                        &format!("{}\n", k.menuitem(ctx, Action::Cast, "Cast spell")),
+                    );
                 }
-                self.wprintw_center(self.statwin, "|[N]||[S]||[W]||[E]| to retreat\n");
+                self.wprintw_center(
+                    self.statwin,
+                    &format!(
+                        "{}{}{}{} to retreat\n",
+                        k.keyitem(ctx, Action::North),
+                        k.keyitem(ctx, Action::South),
+                        k.keyitem(ctx, Action::West),
+                        k.keyitem(ctx, Action::East)
+                    ),
+                );
+
+                self.fight_status();
             }
             StatMode::Spell => {
-                self.mvwprintw_center(self.statwin, 10, "|[W]|eb\n");
-                self.wprintw_center(self.statwin, "|[F]|ireball\n");
-                self.wprintw_center(self.statwin, "|[D]|eathspell\n\n");
-                self.wprintw_center(self.statwin, "|[N]|othing\n\n");
+                let ctx = KeyContext::Spell;
+
+                self.mvwprintw_center(
+                    self.statwin,
+                    10,
+                    &format!("{}\n", k.menuitem(ctx, Action::Web, "Web")),
+                );
+                self.wprintw_center(
+                    self.statwin,
+                    &format!("{}\n", k.menuitem(ctx, Action::Fireball, "Fireball")),
+                );
+                self.wprintw_center(
+                    self.statwin,
+                    &format!("{}\n\n", k.menuitem(ctx, Action::Deathspell, "Deathspell")),
+                );
+                self.wprintw_center(
+                    self.statwin,
+                    &format!("{}\n\n", k.menuitem(ctx, Action::Nothing, "Nothing")),
+                );
+
+                self.fight_status();
             }
             StatMode::Pool => {
-                self.mvwprintw_center(self.statwin, 10, "|[D]|rink");
+                self.mvwprintw_center(
+                    self.statwin,
+                    10,
+                    &k.menuitem(KeyContext::Explore, Action::Down, "Drink"),
+                );
             }
             StatMode::StairsUp => {
-                self.mvwprintw_center(self.statwin, 10, "|[U]|p");
+                self.mvwprintw_center(
+                    self.statwin,
+                    10,
+                    &k.menuitem(KeyContext::Explore, Action::Up, "Up"),
+                );
             }
             StatMode::StairsDown => {
-                self.mvwprintw_center(self.statwin, 10, "|[D]|own");
+                self.mvwprintw_center(
+                    self.statwin,
+                    10,
+                    &k.menuitem(KeyContext::Explore, Action::Down, "Down"),
+                );
             }
             StatMode::Book | StatMode::Chest => {
-                self.mvwprintw_center(self.statwin, 10, "|[O]|pen");
+                self.mvwprintw_center(
+                    self.statwin,
+                    10,
+                    &k.menuitem(KeyContext::Explore, Action::Open, "Open"),
+                );
             }
             StatMode::CrystalOrb => {
-                self.mvwprintw_center(self.statwin, 10, "|[G]|aze");
+                self.mvwprintw_center(
+                    self.statwin,
+                    10,
+                    &k.menuitem(KeyContext::Explore, Action::Gaze, "Gaze"),
+                );
             }
         }
     }
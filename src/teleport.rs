@@ -1,7 +1,10 @@
 use crate::G;
-use ncurses::*;
+use crate::frontend::*;
+use crate::keymap::{Action, KeyContext};
 use std::cmp::Ordering;
 
+use wizardscastle::game::Direction;
+
 impl G {
     /// Print a teleport error message
     pub fn teleport_error(&self) {
@@ -19,68 +22,138 @@ impl G {
         let mut state = 0;
         let mut coord = [0, 0, 0];
 
-        let w = G::popup(12, 28);
+        // Value picked with the movement keys or typed so far, if any
+        let mut pick: Option<u32> = None;
+        let mut typing = false;
+
+        let sizes = [
+            self.game.dungeon_xsize(),
+            self.game.dungeon_ysize(),
+            self.game.dungeon_zsize(),
+        ];
+
+        let w = self.popup(12, 28);
 
         while !done {
             self.wcon(w, G::A_TITLE());
             self.mvwprintw_center(w, 2, "Teleport to where?");
             self.wcoff(w, G::A_TITLE());
 
-            let _ = mvwaddstr(w, 4, 9, "X coord:");
-            let _ = mvwaddstr(w, 5, 9, "Y coord:");
-            let _ = mvwaddstr(w, 6, 9, "Z coord:");
+            self.ui.mvaddstr(w, 4, 9, "X coord:");
+            self.ui.mvaddstr(w, 5, 9, "Y coord:");
+            self.ui.mvaddstr(w, 6, 9, "Z coord:");
 
             for i in 0..3 {
                 match i.cmp(&state) {
                     Ordering::Less => {
-                        wattroff(w, A_REVERSE);
-                        let _ = mvwaddstr(w, 4 + i, 18, &format!("{}", coord[i as usize] + 1));
+                        self.ui.attr_off(w, A_REVERSE);
+                        self.ui
+                            .mvaddstr(w, 4 + i, 18, &format!("{:<2}", coord[i as usize] + 1));
                     }
                     Ordering::Equal => {
-                        wattron(w, A_REVERSE);
-                        let _ = mvwaddstr(w, 4 + i, 18, " ");
-                        wattroff(w, A_REVERSE);
+                        let s = match pick {
+                            Some(v) => format!("{:<2}", v + 1),
+                            None => String::from("  "),
+                        };
+                        self.ui.attr_on(w, A_REVERSE);
+                        self.ui.mvaddstr(w, 4 + i, 18, &s);
+                        self.ui.attr_off(w, A_REVERSE);
+                    }
+                    Ordering::Greater => {
+                        self.ui.mvaddstr(w, 4 + i, 18, "  ");
                     }
-                    _ => (),
                 }
             }
 
-            self.mvwprintw_center(w, 8, "Choose |[1]|-|[8]|\n");
-            self.wprintw_center(w, "or |[N]|evermind");
+            let max = sizes[state as usize];
 
-            box_(w, 0, 0);
+            self.mvwprintw_center(w, 8, &format!("Choose |[1]|-|[{}]|\n", max));
+            self.wprintw_center(
+                w,
+                &format!(
+                    "or {}",
+                    self.keys
+                        .menuitem(KeyContext::Teleport, Action::Nothing, "Nevermind")
+                ),
+            );
 
-            wrefresh(w);
+            self.ui.border(w);
 
-            let ch = G::norm_key(getch());
-            match ch {
-                '1'..='8' => {
-                    let v = ch.to_digit(10).unwrap();
+            self.ui.refresh(w);
 
-                    coord[state as usize] = v - 1;
-                    state += 1;
+            let key = self.getkey();
+            let ch = G::norm_key(key);
 
-                    if state == 3 {
-                        match self.game.teleport(coord[0], coord[1], coord[2]) {
-                            Ok(found_orb_of_zot) => {
-                                if found_orb_of_zot {
-                                    self.update_log_good("GREAT UNMITIGATED ZOT!");
-                                    self.update_log_good("** YOU JUST FOUND THE ORB OF ZOT! **");
-                                    self.update_log("The Runestaff is gone.");
-                                }
-                            }
-                            Err(err) => panic!("{:#?}", err),
+            let mut chosen = None;
+
+            // Digits always pick a coordinate, even if they're movement keys.
+            // The coordinate's chosen as soon as another digit couldn't fit.
+            if let Some(d) = ch.to_digit(10) {
+                let n = if typing {
+                    pick.map_or(0, |v| v + 1) * 10 + d
+                } else {
+                    d
+                };
+
+                if (1..=max).contains(&n) {
+                    pick = Some(n - 1);
+                    typing = true;
+
+                    if n * 10 > max {
+                        chosen = pick;
+                    }
+                }
+            } else {
+                typing = false;
+
+                match self.key_dir(key) {
+                    Some(Direction::North) => pick = Some(pick.map_or(0, |v| (v + 1) % max)),
+                    Some(Direction::South) => {
+                        pick = Some(pick.map_or(max - 1, |v| (v + max - 1) % max))
+                    }
+                    Some(Direction::East) => chosen = pick,
+                    Some(Direction::West) => {
+                        if state > 0 {
+                            state -= 1;
+                            pick = Some(coord[state as usize]);
                         }
+                    }
+                    None => {
+                        if key == '\n' as i32 || key == KEY_ENTER {
+                            chosen = pick;
+                        } else if self.keys.action(KeyContext::Teleport, ch)
+                            == Some(Action::Nothing)
+                        {
+                            done = true;
+                        }
+                    }
+                }
+            }
 
-                        done = true;
+            if let Some(v) = chosen {
+                coord[state as usize] = v;
+                state += 1;
+                pick = None;
+                typing = false;
+
+                if state == 3 {
+                    match self.game.teleport(coord[0], coord[1], coord[2]) {
+                        Ok(found_orb_of_zot) => {
+                            if found_orb_of_zot {
+                                self.update_log_good("GREAT UNMITIGATED ZOT!");
+                                self.update_log_good("** YOU JUST FOUND THE ORB OF ZOT! **");
+                                self.update_log("The Runestaff is gone.");
+                            }
+                        }
+                        Err(err) => panic!("{:#?}", err),
                     }
+
+                    done = true;
                 }
-                'N' => done = true,
-                _ => (),
             }
         }
 
-        G::popup_close(w);
+        self.popup_close(w);
 
         self.redraw_underwins();
     }
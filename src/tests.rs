@@ -0,0 +1,1114 @@
+//! Headless tests
+//!
+//! These drive `G` on a `Headless` frontend with scripted keys and compare
+//! what ends up on the screen with the golden screens in `golden/`. In a
+//! golden screen, `*` matches any run of characters on that line, for the
+//! numbers the backend rolls.
+//!
+//! Run with `WIZCASN_BLESS=1` to rewrite the golden screens from the current
+//! output. You'll have to put the `*`s back by hand.
+use crate::achievements::Achievement;
+use crate::bestiary::Bestiary;
+use crate::career::{Career, Tally};
+use crate::fight::Fight;
+use crate::frontend::*;
+use crate::headless::{Headless, SNAPSHOT};
+use crate::keymap::Keymap;
+use crate::knowledge::Source;
+use crate::scores::Ending;
+use crate::size::DEFAULT_SIZE;
+use crate::stat::StatMode;
+use crate::travel::{Loc, PathMap, Step, Travel, Trip, is_quiet};
+use crate::{G, Opts};
+
+use std::collections::{HashMap, VecDeque};
+use std::env;
+use std::fs;
+use std::ops::Range;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use wizardscastle::game::{Direction, Event, Stairs};
+use wizardscastle::monster::MonsterType;
+use wizardscastle::room::RoomType;
+
+/// Keys to make a Hobbit with chainmail, a mace, and a lamp
+///
+/// No points go to strength or intelligence, and the descending digits put
+/// all the rest in dexterity, however many there are.
+const HOBBIT: &str = "HM009876543210CMY";
+
+/// An empty data directory of a test's own, so tests don't see the
+/// player's files or each other's, removed when the test is done
+struct DataDir(PathBuf);
+
+impl DataDir {
+    fn new() -> DataDir {
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+        let dir = env::temp_dir().join(format!(
+            "wizcasn-test-{}-{}",
+            std::process::id(),
+            COUNT.fetch_add(1, Ordering::Relaxed)
+        ));
+
+        let _ = fs::remove_dir_all(&dir);
+
+        DataDir(dir)
+    }
+}
+
+impl Drop for DataDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+fn opts(dir: &DataDir) -> Opts {
+    Opts {
+        discover_all: false,
+        force_bw: false,
+        give_orb_of_zot: false,
+        give_runestaff: false,
+        locations: false,
+        movement: None,
+        size: Some(DEFAULT_SIZE),
+        scores: None,
+        profile: None,
+        data_dir: Some(dir.0.to_string_lossy().to_string()),
+    }
+}
+
+/// Build a game on an 80x25 headless screen, with its data directory
+fn harness() -> (G, Headless, DataDir) {
+    let dir = DataDir::new();
+    let ui = Headless::new(25, 80, true);
+
+    let g = G::new(Box::new(ui.clone()), opts(&dir), Keymap::new());
+
+    (g, ui, dir)
+}
+
+/// Start a new game and go through character creation
+fn new_hobbit(g: &mut G, ui: &Headless) {
+    new_hobbit_flares(g, ui, false);
+}
+
+/// Make a Hobbit like `new_hobbit()`, spending whatever gold is left on
+/// flares if `broke`
+fn new_hobbit_flares(g: &mut G, ui: &Headless, broke: bool) {
+    ui.push_str(HOBBIT);
+
+    g.restart(true);
+
+    g.choose_class();
+    g.choose_gender();
+    g.choose_stats();
+    g.choose_armor();
+    g.choose_weapon();
+    g.choose_lamp();
+
+    let gp = g.game.player_gp();
+
+    if gp > 0 {
+        ui.push_str(&format!("{}\n", if broke { gp } else { 0 }));
+    }
+
+    g.choose_flares();
+}
+
+/// Tell if a line matches a golden line, where `*` matches anything
+fn glob(pattern: &[char], text: &[char]) -> bool {
+    match pattern.split_first() {
+        None => text.is_empty(),
+        Some(('*', rest)) => (0..=text.len()).any(|i| glob(rest, &text[i..])),
+        Some((p, rest)) => text
+            .split_first()
+            .is_some_and(|(t, text_rest)| p == t && glob(rest, text_rest)),
+    }
+}
+
+/// Compare some rows of a screen with a golden screen
+fn check_golden(name: &str, screen: &[String], rows: Range<usize>) {
+    let path: PathBuf = [
+        env!("CARGO_MANIFEST_DIR"),
+        "golden",
+        &format!("{}.txt", name),
+    ]
+    .iter()
+    .collect();
+
+    if env::var_os("WIZCASN_BLESS").is_some() {
+        // Only bless from tests that look at the whole screen
+        if rows.len() < screen.len() {
+            return;
+        }
+
+        let text: String = screen
+            .iter()
+            .map(|l| format!("{}\n", l.trim_end()))
+            .collect();
+
+        fs::write(&path, text).unwrap();
+        return;
+    }
+
+    let golden =
+        fs::read_to_string(&path).unwrap_or_else(|err| panic!("{}: {}", path.display(), err));
+
+    let golden: Vec<&str> = golden.lines().collect();
+
+    assert_eq!(golden.len(), screen.len(), "{}: wrong height", name);
+
+    let bad: Vec<usize> = rows
+        .filter(|&i| {
+            let pattern: Vec<char> = golden[i].trim_end().chars().collect();
+            let text: Vec<char> = screen[i].trim_end().chars().collect();
+
+            !glob(&pattern, &text)
+        })
+        .collect();
+
+    if !bad.is_empty() {
+        panic!(
+            "{}: rows {:?} don't match the golden screen. Got:\n{}",
+            name,
+            bad,
+            screen.join("\n")
+        );
+    }
+}
+
+#[test]
+fn glob_matches() {
+    let g = |p: &str, t: &str| {
+        glob(
+            &p.chars().collect::<Vec<_>>(),
+            &t.chars().collect::<Vec<_>>(),
+        )
+    };
+
+    assert!(g("ST:*IQ:*", "ST:4   IQ:12"));
+    assert!(g("*", ""));
+    assert!(g("|*|", "|  |"));
+    assert!(!g("ST:*IQ", "ST:4   DX"));
+    assert!(!g("abc", "abcd"));
+}
+
+#[test]
+fn keymap_parse() {
+    // What each file should do: `Ok` with whether --More-- is on, or the
+    // start of the error
+    let cases: [(&str, Result<bool, &str>); 11] = [
+        ("", Ok(true)),
+        ("# nothing but a comment\n\n   \n", Ok(true)),
+        (
+            "[exploration]  # the main screen\nnorth = \"Y\"  # up\n",
+            Ok(true),
+        ),
+        ("[settings]\nmore = \"off\"\n", Ok(false)),
+        (
+            "[settings]\nmore = \"sometimes\"\n",
+            Err("line 2: expected"),
+        ),
+        (
+            "[settings]\nmovement = \"emacs\"\n",
+            Err("line 2: unknown movement"),
+        ),
+        (
+            "[exploration]\nfly = \"Y\"\n",
+            Err("line 2: unknown action"),
+        ),
+        (
+            "[exploration]\n\nquit = \"N\"\n",
+            Err("\"N\" is bound to both"),
+        ),
+        (
+            "[combat]\nbribe = [\"A\", \"B\"]\n",
+            Err("\"A\" is bound to both"),
+        ),
+        (
+            "[settings]\nmovement = \"vi\"\n[exploration]\nlegend = \"K\"\n",
+            Err("\"K\" moves north with vi movement"),
+        ),
+        (
+            "[settings]\nmovement = \"keypad\"\n[lamp]\nnorth = [\"N\", \"8\"]\n",
+            Ok(true),
+        ),
+    ];
+
+    for (text, want) in cases {
+        let mut keys = Keymap::new();
+
+        match (keys.parse(text), want) {
+            (Ok(()), Ok(more)) => assert_eq!(keys.more, more, "{:?}", text),
+            (Err(err), Err(start)) => assert!(err.starts_with(start), "{:?}: {}", text, err),
+            (got, _) => panic!("{:?}: expected {:?}, got {:?}", text, want, got),
+        }
+    }
+}
+
+#[test]
+fn stats_popup() {
+    let (mut g, ui, _dir) = harness();
+
+    ui.push_str("HM");
+
+    g.restart(true);
+    g.choose_class();
+    g.choose_gender();
+
+    ui.push_keys(&[SNAPSHOT]);
+    ui.push_str("009876543210");
+
+    g.choose_stats();
+
+    check_golden("stats", &ui.snapshots()[0], 0..25);
+}
+
+#[test]
+fn main_screen() {
+    let (mut g, ui, _dir) = harness();
+
+    new_hobbit(&mut g, &ui);
+
+    g.update_log_attr("You enter the castle and begin!\n", g.wcget("bold-yellow"));
+    g.update_map(false);
+    g.update_stat();
+
+    check_golden("main", &ui.screen(), 0..25);
+
+    // The player's room is bracketed in bold, and unknown rooms are dim
+    assert_eq!(ui.cell(1, 19), ('<', A_BOLD));
+    assert_eq!(ui.cell(1, 21), ('>', A_BOLD));
+    assert_eq!(ui.cell(1, 2), ('?', A_DIM));
+}
+
+#[test]
+fn big_castle() {
+    let (mut g, ui, _dir) = harness();
+
+    g.options.size = Some((20, 20, 10));
+
+    new_hobbit(&mut g, &ui);
+
+    g.update_map(false);
+
+    let screen = ui.screen();
+
+    // Packed together, with the view cut off at 15 rooms each way
+    assert_eq!(ui.cell(1, 10), ('<', A_BOLD));
+    assert_eq!(ui.cell(1, 12), ('>', A_BOLD));
+    assert!(screen[16].contains(" x 1-15  y 1-15 "));
+}
+
+#[test]
+fn browse_levels() {
+    let (mut g, ui, _dir) = harness();
+
+    new_hobbit(&mut g, &ui);
+
+    g.update_map(false);
+
+    ui.push_keys(&[KEY_NPAGE, SNAPSHOT]);
+    ui.push_str("x");
+
+    assert_eq!(g.browse_levels(']' as i32), 'x' as i32);
+
+    let browsing = &ui.snapshots()[0];
+
+    assert!(browsing[0].contains(" Level 3 of 8 - you're on 1 "));
+    assert!(!browsing[1].contains('<'));
+
+    // Back to the live view
+    assert!(!ui.screen()[0].contains("Level"));
+    assert_eq!(ui.cell(1, 19), ('<', A_BOLD));
+}
+
+#[test]
+fn atlas() {
+    let dir = DataDir::new();
+    let ui = Headless::new(25, 130, true);
+    let mut g = G::new(Box::new(ui.clone()), opts(&dir), Keymap::new());
+
+    new_hobbit(&mut g, &ui);
+
+    g.in_castle = true;
+    g.update_log("You enter the castle and begin!");
+    g.update_map(false);
+
+    let screen = ui.screen();
+
+    // Four levels across, two down, with the player on level 1
+    assert!(screen[0][80..].starts_with("+- Atlas "));
+    assert_eq!(&screen[1][82..89], "Level 1");
+    assert_eq!(ui.cell(1, 82).1, A_BOLD | A_REVERSE);
+    assert_eq!(ui.cell(2, 85), ('@', A_BOLD));
+    assert_eq!(&screen[11][112..119], "Level 8");
+
+    // The log stops short of it
+    assert!(screen[24][..80].ends_with("-+"));
+
+    // Not enough room for it any more
+    ui.resize_screen(25, 100);
+    ui.push_str("x");
+
+    g.getkey();
+
+    assert!(!ui.screen()[0].contains("Atlas"));
+    assert!(ui.screen()[24].ends_with("-+"));
+}
+
+#[test]
+fn legend_from_help() {
+    let (mut g, ui, _dir) = harness();
+
+    new_hobbit(&mut g, &ui);
+
+    ui.push_str("/");
+    ui.push_keys(&[SNAPSHOT]);
+    ui.push_str(" ");
+
+    g.help();
+
+    let legend = ui.snapshots()[0].join("\n");
+
+    for text in [
+        "Map legend",
+        ".   An empty room",
+        "E   The entrance",
+        "G   Gold pieces",
+        "?   A room you haven't seen",
+        "< >  You are here",
+    ] {
+        assert!(legend.contains(text), "no \"{}\" in:\n{}", text, legend);
+    }
+}
+
+#[test]
+fn notes() {
+    let (mut g, ui, _dir) = harness();
+
+    new_hobbit(&mut g, &ui);
+
+    // One room east, marked with ! and a note
+    ui.push_keys(&[KEY_RIGHT]);
+    ui.push_str("\n!vendor angry\n");
+
+    g.annotate();
+
+    assert_eq!(ui.cell(1, 27), ('!', CYAN | A_BOLD));
+
+    // A note with the default mark a level down
+    ui.push_keys(&[KEY_NPAGE]);
+    ui.push_str("\n\n\n");
+
+    g.annotate();
+
+    ui.push_keys(&[SNAPSHOT]);
+    ui.push_str(" ");
+
+    g.notes_popup();
+
+    let journal = ui.snapshots()[0].join("\n");
+
+    assert!(journal.contains("Level 1  ( 5, 1)  !  vendor angry"));
+    assert!(journal.contains("Level 2  ( 4, 1)  *"));
+}
+
+#[test]
+fn look() {
+    let (mut g, ui, _dir) = harness();
+
+    new_hobbit(&mut g, &ui);
+
+    g.record_sighting(4, 0, 0, Source::Lamp, Some(&RoomType::Chest));
+
+    ui.push_keys(&[KEY_RIGHT, SNAPSHOT, 27]);
+
+    g.look();
+
+    let stat: Vec<String> = ui.snapshots()[0]
+        .iter()
+        .map(|l| l.chars().skip(48).collect())
+        .collect();
+    let stat = stat.join("\n");
+
+    assert!(stat.contains("Looking"));
+    assert!(stat.contains("(5,1) level 1"));
+    assert!(stat.contains("Unexplored"));
+    assert!(stat.contains("Lamp T"));
+    assert!(stat.contains("a chest"));
+}
+
+#[test]
+fn rumors() {
+    let (mut g, ui, _dir) = harness();
+
+    new_hobbit(&mut g, &ui);
+
+    // Two rooms east
+    g.record_sighting(5, 0, 0, Source::Orb, Some(&RoomType::Gold));
+    g.record_sighting(2, 3, 1, Source::Orb, None);
+    g.update_map(false);
+
+    assert_eq!(ui.cell(1, 32), ('G', GREEN | A_DIM));
+
+    ui.push_keys(&[SNAPSHOT]);
+    ui.push_str(" ");
+
+    g.rumors_popup();
+
+    let popup = ui.snapshots()[0].join("\n");
+
+    assert!(popup.contains("Level 1  ( 6, 1)  Orb   gold pieces"));
+    assert!(popup.contains("Level 2  ( 3, 4)  Orb   the Orb of Zot"));
+    assert!(popup.contains("unconfirmed"));
+}
+
+/// An 8x8 castle of empty rooms two levels deep, with the entrance where it
+/// always is and `rooms` put in
+fn castle(rooms: &[(Loc, RoomType)]) -> HashMap<Loc, RoomType> {
+    let mut castle = HashMap::from([((3, 0, 0), RoomType::Entrance)]);
+
+    castle.extend(rooms.iter().cloned());
+
+    castle
+}
+
+/// A map of a castle made by `castle()`, with everything on it but `unknown`
+fn path_map<'a>(castle: &'a HashMap<Loc, RoomType>, unknown: &'a [Loc]) -> PathMap<'a> {
+    PathMap {
+        size: (8, 8, 2),
+        room: Box::new(|loc| castle.get(&loc).cloned().unwrap_or(RoomType::Empty)),
+        known: Box::new(|loc| !unknown.contains(&loc)),
+    }
+}
+
+#[test]
+fn travel_paths() {
+    use Direction::*;
+
+    let rooms = castle(&[
+        ((4, 0, 0), RoomType::Warp(false)),
+        ((5, 5, 0), RoomType::StairsDown),
+    ]);
+    let map = path_map(&rooms, &[(2, 0, 0)]);
+
+    let path = |start: Loc, goal: Loc| {
+        map.find_path(start, |loc| loc == goal, |loc| is_quiet(&(map.room)(loc)))
+            .map(|(_, path)| Vec::from(path))
+    };
+
+    // Around the warp, and not north out of the entrance
+    assert_eq!(
+        path((3, 0, 0), (5, 0, 0)),
+        Some(vec![
+            Step::Dir(South),
+            Step::Dir(East),
+            Step::Dir(East),
+            Step::Dir(North)
+        ])
+    );
+
+    // Off one edge and back on the other
+    assert_eq!(path((0, 3, 0), (7, 3, 0)), Some(vec![Step::Dir(West)]));
+
+    // Down the stairs
+    let down = path((3, 0, 0), (5, 5, 1)).unwrap();
+
+    assert_eq!(down.len(), 8);
+    assert_eq!(down.last(), Some(&Step::Stairs(Stairs::Down)));
+
+    // A room that isn't on the map can be the goal, but not on the way
+    assert_eq!(path((3, 0, 0), (2, 0, 0)), Some(vec![Step::Dir(West)]));
+    assert_eq!(path((3, 0, 0), (1, 0, 0)).map(|p| p.len()), Some(4));
+
+    // No way at all, with a warp and a sinkhole in the way
+    let blocked = castle(&[
+        ((3, 1, 0), RoomType::Warp(false)),
+        ((4, 0, 0), RoomType::Sinkhole),
+    ]);
+    let map = path_map(&blocked, &[(2, 0, 0)]);
+
+    assert!(
+        map.find_path(
+            (3, 0, 0),
+            |loc| loc == (5, 0, 0),
+            |loc| is_quiet(&(map.room)(loc))
+        )
+        .is_none()
+    );
+}
+
+#[test]
+fn travel() {
+    let (mut g, ui, _dir) = harness();
+
+    new_hobbit(&mut g, &ui);
+
+    // The map cursor starts on the player
+    ui.push_str(";\n");
+
+    g.travel();
+
+    let last = g.messages.borrow().last().unwrap().text.clone();
+    assert_eq!(last, "** You're already there!");
+    assert!(g.travel.is_none());
+
+    // A key stops a trip
+    let path = VecDeque::from([Step::Dir(Direction::South)]);
+
+    g.travel = Some(Travel::new(Trip::Route(path), g.messages.borrow().len()));
+    ui.push_str("x");
+
+    assert!(g.next_travel_step().is_none());
+    assert!(g.travel.is_none());
+
+    let last = g.messages.borrow().last().unwrap().text.clone();
+    assert_eq!(last, "You stop traveling.");
+}
+
+#[test]
+fn explore_steps() {
+    use Direction::*;
+
+    // Everything's been seen on the first level but a room among pools
+    let rooms = castle(&[
+        ((6, 5, 0), RoomType::Pool),
+        ((6, 7, 0), RoomType::Pool),
+        ((5, 6, 0), RoomType::Pool),
+        ((7, 6, 0), RoomType::Pool),
+        ((5, 5, 0), RoomType::StairsDown),
+    ]);
+    let map = path_map(&rooms, &[]);
+
+    let unseen = |loc: Loc| loc == (6, 6, 0) || loc.2 == 1;
+
+    // Exploring doesn't go through pools to get there, and the level below
+    // doesn't count
+    assert_eq!(map.explore_step((3, 0, 0), |loc| !unseen(loc)), None);
+
+    // The nearest room not seen, going around the pools
+    let rooms = castle(&[((6, 5, 0), RoomType::Pool), ((6, 7, 0), RoomType::Pool)]);
+    let map = path_map(&rooms, &[]);
+
+    assert_eq!(
+        map.explore_step((6, 4, 0), |loc| !unseen(loc)),
+        Some(Step::Dir(West))
+    );
+    assert_eq!(
+        map.explore_step((3, 0, 0), |loc| !unseen(loc) && loc != (3, 1, 0)),
+        Some(Step::Dir(South))
+    );
+}
+
+#[test]
+fn explore() {
+    let (mut g, ui, _dir) = harness();
+
+    new_hobbit(&mut g, &ui);
+
+    g.explore();
+
+    assert_ne!(g.player_loc(), (3, 0, 0));
+
+    while let Some(step) = g.next_travel_step() {
+        g.take_step(step);
+        g.travel_event(&Event::None);
+    }
+
+    // Whatever the castle's like, exploring only ends when there's nowhere
+    // left to go
+    assert_eq!(g.explore_step(), None);
+
+    let last = g.messages.borrow().last().unwrap().text.clone();
+    assert_eq!(last, "You've explored all you can reach on this level.");
+
+    // Gold doesn't stop exploring, but a sinkhole does, and says so
+    g.travel = Some(Travel::new(Trip::Explore, g.messages.borrow().len()));
+
+    g.travel_event(&Event::FoundGold(10));
+    assert!(g.travel.is_some());
+
+    g.travel_event(&Event::Sinkhole);
+    assert!(g.travel.is_none());
+
+    let last = g.messages.borrow().last().unwrap().text.clone();
+    assert_eq!(last, "You stop exploring: you fell into a sinkhole.");
+}
+
+#[test]
+fn run() {
+    let (mut g, ui, _dir) = harness();
+
+    new_hobbit(&mut g, &ui);
+
+    // Not out of the entrance
+    ui.push_keys(&[KEY_UP]);
+
+    g.start_run();
+
+    assert_eq!((g.game.player_x(), g.game.player_y()), (3, 0));
+    assert!(g.travel.is_none());
+
+    // East until something turns up: every room run through is empty, and
+    // it stops in the first one that isn't, the entrance at the latest
+    ui.push_keys(&[KEY_RIGHT]);
+
+    g.start_run();
+    g.travel_event(&Event::None);
+
+    let mut steps = 1;
+
+    while let Some(step) = g.next_travel_step() {
+        assert_eq!(*g.game.room_at_player().room_type(), RoomType::Empty);
+
+        g.take_step(step);
+        g.travel_event(&Event::None);
+        steps += 1;
+    }
+
+    assert!(steps <= 8);
+    assert_eq!(g.game.player_y(), 0);
+    assert_ne!(*g.game.room_at_player().room_type(), RoomType::Empty);
+
+    // Any message stops a run
+    ui.push_keys(&[KEY_DOWN]);
+
+    g.start_run();
+    g.update_log("You see a bat fly by.");
+
+    assert!(g.next_travel_step().is_none());
+    assert_eq!(g.game.player_y(), 1);
+}
+
+#[test]
+fn high_scores() {
+    let (mut g, ui, _dir) = harness();
+
+    new_hobbit(&mut g, &ui);
+
+    let path = env::temp_dir().join(format!("wizcasn-scores-{}", std::process::id()));
+    let _ = fs::remove_file(&path);
+
+    g.options.scores = Some(path.to_string_lossy().to_string());
+
+    let died = g.score(Ending::Died);
+    let escaped = g.score(Ending::Escaped);
+
+    assert!(escaped > died);
+
+    let (_, rank) = g.record_score(Ending::Died).unwrap();
+    assert_eq!(rank, Some(0));
+
+    let (scores, rank) = g.record_score(Ending::Escaped).unwrap();
+    assert_eq!(rank, Some(0));
+    assert_eq!(scores.len(), 2);
+    assert_eq!(scores[1].points, died);
+
+    // Ties go after the scores that were there first
+    let (_, rank) = g.record_score(Ending::Died).unwrap();
+    assert_eq!(rank, Some(2));
+
+    assert_eq!(g.load_scores().len(), 3);
+
+    ui.push_keys(&[SNAPSHOT]);
+    ui.push_str(" ");
+
+    g.hall_of_fame(&g.load_scores(), Some(1));
+
+    let screen = ui.snapshots()[0].join("\n");
+
+    assert!(screen.contains("Hall of Fame"));
+    assert!(screen.contains(&format!("  1. {:>6}", escaped)));
+    assert!(screen.contains("died on level 1"));
+
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn career() {
+    let mut c = Career::parse("wizcasn-career 1\ngames 2\nquit 2\nkilled 3 orc\nsomething new 7\n");
+
+    let mut tally = Tally::default();
+    tally.kill("orc", false, 100);
+    tally.kill("balrog", false, 50);
+    tally.kill("vendor", true, 0);
+    tally.treasure();
+    tally.gold(25);
+
+    c.add(Ending::Died, Some("strength"), 100, &tally);
+
+    assert_eq!(c.games, 3);
+    assert_eq!(c.died.get("strength"), Some(&1));
+    assert_eq!(c.kills.get("orc"), Some(&4));
+    assert_eq!(c.kills.get("vendor"), None);
+    assert_eq!(c.vendors, 1);
+    assert_eq!(c.gold, 175);
+    assert_eq!(c.treasures, 1);
+    assert_eq!(c.turns, 100);
+
+    // The unknown line is dropped, and everything else comes back
+    assert_eq!(Career::parse(&c.to_text()), c);
+}
+
+#[test]
+fn career_endings() {
+    let tally = Tally::default();
+    let mut c = Career::default();
+
+    c.add(Ending::Won, None, 10, &tally);
+    c.add(Ending::Escaped, None, 10, &tally);
+    c.add(Ending::Escaped, None, 10, &tally);
+    c.add(Ending::Quit, None, 10, &tally);
+
+    for cause in ["strength", "intelligence", "dexterity", "strength"] {
+        c.add(Ending::Died, Some(cause), 10, &tally);
+    }
+
+    c.add(Ending::Died, None, 10, &tally);
+
+    assert_eq!(c.games, 9);
+    assert_eq!((c.won, c.escaped, c.quit), (1, 2, 1));
+    assert_eq!(c.died.get("strength"), Some(&2));
+    assert_eq!(c.died.get("intelligence"), Some(&1));
+    assert_eq!(c.died.get("dexterity"), Some(&1));
+    assert_eq!(c.died.get("other"), Some(&1));
+    assert_eq!(c.turns, 90);
+
+    assert_eq!(Career::parse(&c.to_text()), c);
+}
+
+#[test]
+fn career_file() {
+    let (mut g, ui, _dir) = harness();
+
+    new_hobbit(&mut g, &ui);
+
+    assert_eq!(g.load_career(), Career::default());
+
+    g.record_career(Ending::Quit);
+    g.record_career(Ending::Won);
+
+    let c = g.load_career();
+
+    assert_eq!((c.games, c.won, c.quit), (2, 1, 1));
+
+    // Cheating doesn't count
+    g.options.give_runestaff = true;
+    g.record_career(Ending::Won);
+
+    assert_eq!(g.load_career(), c);
+}
+
+#[test]
+fn achievements() {
+    let (mut g, ui, _dir) = harness();
+
+    new_hobbit(&mut g, &ui);
+
+    // The first time there's a popup to dismiss
+    ui.push_keys(&[SNAPSHOT]);
+    ui.push_str(" ");
+
+    g.achieve(Achievement::VendorKiller);
+
+    assert_eq!(ui.snapshots().len(), 1);
+
+    let unlocked = g.load_achievements();
+
+    assert_eq!(
+        unlocked.keys().copied().collect::<Vec<_>>(),
+        ["vendor-killer"]
+    );
+
+    // The second time there isn't, and nothing changes
+    g.achieve(Achievement::VendorKiller);
+
+    assert_eq!(g.load_achievements(), unlocked);
+
+    // Cheating earns nothing, and doesn't wait for a key either
+    g.options.locations = true;
+    g.achieve(Achievement::OpalEye);
+
+    assert_eq!(g.load_achievements(), unlocked);
+}
+
+#[test]
+fn bestiary() {
+    let (mut g, ui, _dir) = harness();
+
+    let mut b = Bestiary::parse("wizcasn-bestiary 1\nbeast orc 3 2 0 1 40 12\ndish orc stew\n");
+
+    let mut game = Bestiary::default();
+    game.entry("orc").met += 1;
+    game.entry("troll").taken += 7;
+    game.dishes.insert(String::from("trollwich"));
+    game.dishes.insert(String::from("orc stew"));
+
+    b.add(&game);
+
+    assert_eq!(b.beasts["orc"].met, 4);
+    assert_eq!(b.beasts["troll"].taken, 7);
+    assert_eq!(b.dishes.len(), 2);
+    assert_eq!(Bestiary::parse(&b.to_text()), b);
+
+    // The test's data directory starts out empty, so only what's met this
+    // game shows up, before it's saved
+    assert_eq!(g.load_bestiary(), Bestiary::default());
+
+    g.beasts = game.clone();
+
+    ui.push_keys(&[SNAPSHOT]);
+    ui.push_str(" ");
+
+    g.bestiary_popup();
+
+    let screen = ui.snapshots()[0].join("\n");
+
+    assert!(screen.contains("Bestiary: 2 of 13 met"));
+    assert!(screen.contains("Trollwich"));
+
+    // Saving it forgets it, so it's only counted once
+    g.record_bestiary();
+    g.record_bestiary();
+
+    assert!(g.beasts.beasts.is_empty());
+    assert_eq!(g.load_bestiary(), game);
+}
+
+#[test]
+fn fight_feedback() {
+    let (mut g, ui, _dir) = harness();
+
+    new_hobbit(&mut g, &ui);
+
+    // An orc is second in line, so it should have 4 hit points
+    g.fight = Some(Fight::new(MonsterType::Orc));
+    g.fight_hit(3);
+    g.fight_miss();
+    g.fight_hurt(2, 1);
+
+    g.set_statmode(StatMode::Combat);
+
+    let screen = ui.screen().join("\n");
+
+    assert!(screen.contains("Orc: ~1 of 4 HP"));
+    assert!(screen.contains("It hit for 2, -1 ST"));
+
+    g.end_fight();
+
+    assert!(g.fight.is_none());
+    assert_eq!(
+        g.messages.borrow().last().unwrap().text,
+        "That fight: you dealt 3 in 1 hit (1 miss) and took 2, 1 of it from strength."
+    );
+
+    // A fight that never got going isn't worth a summary
+    let count = g.messages.borrow().len();
+
+    g.fight = Some(Fight::new(MonsterType::Dragon));
+    g.end_fight();
+
+    assert_eq!(g.messages.borrow().len(), count);
+}
+
+#[test]
+fn parse_size() {
+    assert_eq!(G::parse_size("12x12x10"), Some((12, 12, 10)));
+    assert_eq!(G::parse_size("8X8X8"), Some((8, 8, 8)));
+    assert_eq!(G::parse_size("7x8x8"), None);
+    assert_eq!(G::parse_size("8x8"), None);
+    assert_eq!(G::parse_size("8xax8"), None);
+}
+
+#[test]
+fn resize() {
+    let (mut g, ui, _dir) = harness();
+
+    new_hobbit(&mut g, &ui);
+
+    g.in_castle = true;
+
+    g.update_log("You enter the castle and begin!");
+    g.update_map(false);
+    g.update_stat();
+
+    let before = ui.screen();
+
+    // Bigger: the log fills the extra space
+    ui.resize_screen(30, 100);
+    ui.push_str("x");
+
+    assert_eq!(g.getkey(), 'x' as i32);
+
+    let screen = ui.screen();
+
+    assert!(screen[29].starts_with("+---") && screen[29].ends_with("-+"));
+    assert!(screen[18].contains("You enter the castle and begin!"));
+    assert_eq!(screen[1][..80], before[1]);
+
+    // Too small, then back to normal
+    ui.resize_screen(20, 60);
+    ui.push_keys(&[SNAPSHOT]);
+    ui.resize_screen(25, 80);
+    ui.push_str("x");
+
+    assert_eq!(g.getkey(), 'x' as i32);
+
+    assert!(ui.snapshots()[0].iter().any(|l| l.contains("too small")));
+
+    check_golden("main", &ui.screen(), 0..17);
+}
+
+#[test]
+fn log_scrolls() {
+    let (mut g, ui, _dir) = harness();
+
+    g.keys.more = false;
+
+    for i in 1..=10 {
+        g.update_log(&format!("Message {}", i));
+    }
+
+    let screen = ui.screen();
+
+    for (row, i) in (18..24).zip(5..=10) {
+        assert_eq!(screen[row][1..79].trim_end(), format!("Message {}", i));
+    }
+
+    assert!(screen[17].starts_with("+---"));
+    assert!(screen[24].starts_with("+---"));
+}
+
+#[test]
+fn more_prompt() {
+    let (mut g, ui, _dir) = harness();
+
+    // The log shows 7 lines, so the 8th message waits for a key
+    ui.push_keys(&[SNAPSHOT]);
+    ui.push_str(" ");
+
+    for i in 1..=8 {
+        g.update_log(&format!("Message {}", i));
+    }
+
+    let waiting = &ui.snapshots()[0];
+
+    assert!(waiting[24].contains("--More--"));
+    assert_eq!(waiting[24 - 1][1..79].trim_end(), "Message 7");
+
+    let screen = ui.screen();
+
+    assert!(!screen[24].contains("--More--"));
+    assert_eq!(screen[23][1..79].trim_end(), "Message 8");
+
+    // A resize at --More-- doesn't print the waiting message twice
+    g.in_castle = true;
+
+    ui.resize_screen(25, 80);
+    ui.push_str(" ");
+
+    for i in 9..=15 {
+        g.update_log(&format!("Message {}", i));
+    }
+
+    let screen = ui.screen();
+
+    assert_eq!(screen[22][1..79].trim_end(), "Message 14");
+    assert_eq!(screen[23][1..79].trim_end(), "Message 15");
+}
+
+#[test]
+fn vendor_too_poor() {
+    let (mut g, ui, _dir) = harness();
+
+    new_hobbit_flares(&mut g, &ui, true);
+
+    assert_eq!(g.game.player_gp(), 0);
+
+    g.vendor_trade();
+
+    assert!(
+        ui.screen()[18..24]
+            .iter()
+            .any(|row| row.contains("** You're too poor to trade, Hobbit."))
+    );
+}
+
+#[test]
+fn vendor_screen() {
+    let (mut g, ui, _dir) = harness();
+
+    new_hobbit_flares(&mut g, &ui, true);
+
+    ui.push_keys(&[SNAPSHOT]);
+    ui.push_str("N");
+
+    g.trade_equipment();
+
+    check_golden("vendor", &ui.snapshots()[0], 0..25);
+}
+
+#[test]
+fn whole_game() {
+    let (mut g, ui, _dir) = harness();
+
+    ui.push_str(" N");
+    ui.push_str(HOBBIT);
+    ui.push_keys(&[SNAPSHOT]);
+    ui.push_str("QY");
+
+    g.run();
+
+    // The log has a random message or two, but the map and stats should
+    // look just like they do when drawn by hand
+    check_golden("main", &ui.snapshots()[0], 0..17);
+}
+
+#[test]
+fn history() {
+    let (mut g, ui, _dir) = harness();
+
+    g.keys.more = false;
+
+    for i in 1..=30 {
+        g.update_log(&format!("Message {}", i));
+    }
+
+    g.update_log_bad("** Ouch!");
+
+    // Search for an old message, then show only the bad ones
+    ui.push_str("/message 3\n");
+    ui.push_keys(&[SNAPSHOT]);
+    ui.push_str("fff");
+    ui.push_keys(&[SNAPSHOT]);
+    ui.push_str("q");
+
+    g.history();
+
+    let snapshots = ui.snapshots();
+
+    let found = &snapshots[0];
+    assert!(found[1].contains("Message history (all messages)"));
+    assert!(found.iter().any(|l| l.contains("Message 30")));
+
+    let bad = &snapshots[1];
+    assert!(bad[1].contains("Message history (bad messages)"));
+    assert!(bad[3].contains("** Ouch!"));
+    assert!(!bad.iter().any(|l| l.contains("Message 1")));
+
+    // A taller screen shows a longer page
+    ui.resize_screen(35, 80);
+    ui.push_str("x");
+    ui.push_keys(&[SNAPSHOT]);
+    ui.push_str("q");
+
+    g.history();
+
+    let tall = &ui.snapshots()[2];
+    assert!(tall[32].contains("[f]ilter"));
+    assert!(tall[30].contains("** Ouch!"));
+    assert!(tall[3].contains("Message 4"));
+}
@@ -0,0 +1,431 @@
+//! Traveling to a room the player has already found, one turn per room
+//!
+//! Paths only go through discovered rooms, and around anything that would
+//! stop the trip anyway, like monsters and warps. The trip stops as soon as
+//! anything happens: a room event, a bad message, or a key.
+use crate::G;
+use crate::frontend::*;
+use crate::history::Severity;
+use crate::keymap::{Action, KeyContext};
+use crate::size::Size;
+
+use std::collections::{HashMap, VecDeque};
+
+use wizardscastle::game::{Direction, Event, Stairs};
+use wizardscastle::room::RoomType;
+
+/// A room in the castle, as (x, y, z)
+pub type Loc = (u32, u32, u32);
+
+/// Milliseconds to wait between steps, so the player can watch
+const STEP_DELAY: i32 = 60;
+
+/// One move along a path
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    Dir(Direction),
+    Stairs(Stairs),
+}
+
+/// What sort of trip it is
+#[derive(Debug, Clone)]
+pub enum Trip {
+    /// Following a path worked out at the start
+    Route(VecDeque<Step>),
+
+    /// Finding the way one step at a time to rooms not seen yet
+    Explore,
+
+    /// Going one way until something turns up
+    Run(Direction),
+}
+
+/// A trip in progress
+#[derive(Debug, Clone)]
+pub struct Travel {
+    pub trip: Trip,
+
+    /// How many messages there were after the last step
+    mark: usize,
+}
+
+impl Travel {
+    /// Start a trip, once there have been `mark` messages
+    pub fn new(trip: Trip, mark: usize) -> Travel {
+        Travel { trip, mark }
+    }
+}
+
+/// The castle, as far as finding a way through it goes
+///
+/// This is apart from the `Game`, so paths can be worked out on any map.
+pub struct PathMap<'a> {
+    pub size: Size,
+
+    /// What's in a room
+    pub room: Box<dyn Fn(Loc) -> RoomType + 'a>,
+
+    /// Tell if a room is on the map
+    pub known: Box<dyn Fn(Loc) -> bool + 'a>,
+}
+
+impl PathMap<'_> {
+    /// The room a step leads to
+    fn step_dest(&self, (x, y, z): Loc, step: Step) -> Loc {
+        let (xs, ys, _) = self.size;
+
+        match step {
+            Step::Dir(Direction::North) => (x, (y + ys - 1) % ys, z),
+            Step::Dir(Direction::South) => (x, (y + 1) % ys, z),
+            Step::Dir(Direction::West) => ((x + xs - 1) % xs, y, z),
+            Step::Dir(Direction::East) => ((x + 1) % xs, y, z),
+            Step::Stairs(Stairs::Up) => (x, y, z - 1),
+            Step::Stairs(Stairs::Down) => (x, y, z + 1),
+        }
+    }
+
+    /// The steps that can be taken out of a room
+    ///
+    /// Walking off the edge of a level comes back on the other side. North
+    /// out of the entrance leaves the castle, so it isn't a way anywhere.
+    fn steps_from(&self, (x, y, z): Loc) -> Vec<Step> {
+        let mut steps = vec![
+            Step::Dir(Direction::North),
+            Step::Dir(Direction::South),
+            Step::Dir(Direction::West),
+            Step::Dir(Direction::East),
+        ];
+
+        match (self.room)((x, y, z)) {
+            RoomType::Entrance => steps.retain(|s| *s != Step::Dir(Direction::North)),
+            RoomType::StairsUp if z > 0 => steps.push(Step::Stairs(Stairs::Up)),
+            RoomType::StairsDown if z + 1 < self.size.2 => steps.push(Step::Stairs(Stairs::Down)),
+            _ => (),
+        }
+
+        steps
+    }
+
+    /// Find the shortest path from `start` to the nearest room that's a goal,
+    /// through known rooms it's OK to go `through`
+    ///
+    /// Returns the goal and how to get there, or `None` if there's no way.
+    pub fn find_path(
+        &self,
+        start: Loc,
+        goal: impl Fn(Loc) -> bool,
+        through: impl Fn(Loc) -> bool,
+    ) -> Option<(Loc, VecDeque<Step>)> {
+        if goal(start) {
+            return Some((start, VecDeque::new()));
+        }
+
+        let mut came_from: HashMap<Loc, (Loc, Step)> = HashMap::new();
+        let mut queue = VecDeque::from([start]);
+
+        while let Some(here) = queue.pop_front() {
+            for step in self.steps_from(here) {
+                let there = self.step_dest(here, step);
+
+                if there == start || came_from.contains_key(&there) {
+                    continue;
+                }
+
+                let found = goal(there);
+
+                if !found && !(self.known)(there) {
+                    continue;
+                }
+
+                came_from.insert(there, (here, step));
+
+                if found {
+                    let mut path = VecDeque::new();
+                    let mut at = there;
+
+                    while at != start {
+                        let (prev, step) = came_from[&at];
+                        path.push_front(step);
+                        at = prev;
+                    }
+
+                    return Some((there, path));
+                }
+
+                if through(there) {
+                    queue.push_back(there);
+                }
+            }
+        }
+
+        None
+    }
+}
+
+/// Tell if a path can go through a room without something happening
+pub fn is_quiet(r: &RoomType) -> bool {
+    !matches!(
+        r,
+        RoomType::Warp(_) | RoomType::Sinkhole | RoomType::Monster(_)
+    )
+}
+
+impl G {
+    /// The castle as the player knows it, for finding paths through
+    pub fn path_map(&self) -> PathMap<'_> {
+        PathMap {
+            size: self.castle_size(),
+            room: Box::new(|(x, y, z)| self.game.dungeon_room_at(x, y, z).roomtype.clone()),
+            known: Box::new(|loc| self.known_room(loc)),
+        }
+    }
+
+    /// Where the player is
+    pub fn player_loc(&self) -> Loc {
+        (
+            self.game.player_x(),
+            self.game.player_y(),
+            self.game.player_z(),
+        )
+    }
+
+    /// Tell if a room is on the map
+    pub fn known_room(&self, (x, y, z): Loc) -> bool {
+        self.options.discover_all || self.game.dungeon_room_at(x, y, z).discovered
+    }
+
+    /// Tell if a path can go through a room without something happening
+    pub fn quiet_room(&self, (x, y, z): Loc) -> bool {
+        is_quiet(&self.game.dungeon_room_at(x, y, z).roomtype)
+    }
+
+    /// Find the shortest path from the player to the nearest room that's a
+    /// goal, through known rooms it's OK to go `through`
+    pub fn find_path(
+        &self,
+        goal: impl Fn(Loc) -> bool,
+        through: impl Fn(Loc) -> bool,
+    ) -> Option<(Loc, VecDeque<Step>)> {
+        self.path_map().find_path(self.player_loc(), goal, through)
+    }
+
+    /// Ask where to travel to, and set off
+    pub fn travel(&mut self) {
+        if self.game.player_is_blind() {
+            self.update_log_error(&format!(
+                "** You can't see where you're going, dumb {}",
+                self.race_name()
+            ));
+            return;
+        }
+
+        let w = self.popup(11, 54);
+
+        self.wcon(w, G::A_TITLE());
+        self.mvwprintw_center(w, 2, "Travel where?");
+        self.wcoff(w, G::A_TITLE());
+
+        self.mvwprintw_center(
+            w,
+            4,
+            &format!(
+                "{} to pick a room on the map,",
+                self.keys.keyitem(KeyContext::Explore, Action::Look)
+            ),
+        );
+        self.mvwprintw_center(w, 5, "or a map symbol for the nearest one of those,");
+        self.mvwprintw_center(w, 6, "like |[D]| for stairs going down");
+        self.mvwprintw_center(w, 8, "|[ESC]| to stay put");
+
+        self.ui.border(w);
+        self.ui.refresh(w);
+
+        let key = self.getkey();
+
+        self.popup_close(w);
+        self.redraw_underwins();
+
+        let look = self.keys.action(KeyContext::Explore, G::norm_key(key)) == Some(Action::Look);
+
+        let found = if key == 27 {
+            return;
+        } else if look || key == '\n' as i32 || key == KEY_ENTER {
+            let Some(dest) = self.pick_room(false) else {
+                return;
+            };
+
+            if !self.known_room(dest) {
+                self.update_log_error("** You can only travel to rooms you've found.");
+                return;
+            }
+
+            self.find_path(|loc| loc == dest, |loc| self.quiet_room(loc))
+        } else {
+            let ch = G::norm_key(key);
+
+            self.find_path(
+                |(x, y, z)| {
+                    let r = self.game.dungeon_room_at(x, y, z);
+
+                    self.known_room((x, y, z))
+                        && G::room_char(&r.roomtype).to_ascii_uppercase() == ch
+                },
+                |loc| self.quiet_room(loc),
+            )
+        };
+
+        let Some(((x, y, z), steps)) = found else {
+            self.update_log_error("** You don't know a way there.");
+            return;
+        };
+
+        if steps.is_empty() {
+            self.update_log_error("** You're already there!");
+            return;
+        }
+
+        self.update_log(&format!(
+            "You set off for {} at ({},{}) level {}.",
+            G::room_name(&self.game.dungeon_room_at(x, y, z).roomtype),
+            x + 1,
+            y + 1,
+            z + 1
+        ));
+
+        self.travel = Some(Travel::new(
+            Trip::Route(steps),
+            self.messages.borrow().len(),
+        ));
+
+        // The first step is this turn
+        if let Some(step) = self.next_travel_step() {
+            self.take_step(step);
+        }
+    }
+
+    /// Take a step of a path
+    pub fn take_step(&mut self, step: Step) {
+        match step {
+            Step::Dir(d) => self.move_dir(d),
+            Step::Stairs(s) => self.move_stairs(s),
+        }
+    }
+
+    /// The next step of the trip, if there's a trip and nothing has come up
+    /// to stop it
+    ///
+    /// Running stops for any message at all, the others just for bad ones.
+    pub fn next_travel_step(&mut self) -> Option<Step> {
+        let travel = self.travel.as_ref()?;
+
+        let news = self.messages.borrow().iter().skip(travel.mark).any(|m| {
+            matches!(travel.trip, Trip::Run(_))
+                || matches!(m.severity, Severity::Bad | Severity::Error)
+        });
+
+        if news {
+            self.travel = None;
+            return None;
+        }
+
+        let step = match &mut self.travel.as_mut()?.trip {
+            Trip::Route(steps) => steps.pop_front(),
+            Trip::Explore => {
+                let step = self.explore_step();
+
+                if step.is_none() {
+                    self.update_log("You've explored all you can reach on this level.");
+                }
+
+                step
+            }
+            Trip::Run(d) => {
+                let d = *d;
+                let r = self.game.room_at_player().room_type();
+
+                // Stop short of leaving the castle
+                (!(*r == RoomType::Entrance && d == Direction::North)).then_some(Step::Dir(d))
+            }
+        };
+
+        if step.is_none() {
+            self.travel = None;
+            return None;
+        }
+
+        if self.poll_interrupt(STEP_DELAY) {
+            self.stop_travel();
+            return None;
+        }
+
+        // Taking the step comes next, and anything it says is news
+        if let Some(t) = self.travel.as_mut() {
+            t.mark = self.messages.borrow().len();
+        }
+
+        step
+    }
+
+    /// End the trip if something happened in the room the player just
+    /// came to
+    ///
+    /// Traveling stops for anything at all, and running for any room that
+    /// isn't empty. Exploring keeps going past gold and flares, and says why
+    /// it stopped.
+    pub fn travel_event(&mut self, event: &Event) {
+        let Some(t) = &self.travel else {
+            return;
+        };
+
+        match t.trip {
+            Trip::Explore => {
+                if let Some(why) = self.explore_stop_reason(event) {
+                    self.travel = None;
+                    self.update_log(&format!("You stop exploring: {}.", why));
+                }
+            }
+            Trip::Run(_) => {
+                if !matches!(event, Event::None)
+                    || *self.game.room_at_player().room_type() != RoomType::Empty
+                {
+                    self.travel = None;
+                }
+            }
+            Trip::Route(_) => {
+                if !matches!(event, Event::None) {
+                    self.travel = None;
+                }
+            }
+        }
+    }
+
+    /// Run one way until something turns up
+    pub fn start_run(&mut self) {
+        let key = self.getkey();
+
+        let dir = match self.key_action(KeyContext::Explore, key) {
+            Some(Action::North) => Direction::North,
+            Some(Action::South) => Direction::South,
+            Some(Action::West) => Direction::West,
+            Some(Action::East) => Direction::East,
+            _ => return,
+        };
+
+        self.travel = Some(Travel::new(Trip::Run(dir), self.messages.borrow().len()));
+
+        // The first step is this turn
+        if let Some(step) = self.next_travel_step() {
+            self.take_step(step);
+        }
+    }
+
+    /// Stop traveling, if the player is
+    pub fn stop_travel(&mut self) {
+        match self.travel.take().map(|t| t.trip) {
+            Some(Trip::Explore) => self.update_log("You stop exploring."),
+            Some(Trip::Run(_)) => self.update_log("You stop running."),
+            Some(Trip::Route(_)) => self.update_log("You stop traveling."),
+            None => (),
+        }
+    }
+}
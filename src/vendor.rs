@@ -1,5 +1,6 @@
 use crate::G;
-use ncurses::*;
+use crate::frontend::*;
+use crate::keymap::{Action, KeyContext};
 
 use wizardscastle::armor::{Armor, ArmorType};
 use wizardscastle::game::Game;
@@ -9,7 +10,7 @@ use wizardscastle::weapon::{Weapon, WeaponType};
 
 impl G {
     /// Helper function to print gold pieces
-    fn print_gps(&self, w: WINDOW) {
+    fn print_gps(&self, w: Win) {
         let gps = self.game.player_gp();
 
         let unit = if gps == 1 { "GP" } else { "GPs" };
@@ -33,7 +34,7 @@ impl G {
         };
 
         let height = (10 + treasures.len()) as i32;
-        let w = G::popup(height, 40);
+        let w = self.popup(height, 40);
 
         let mut sold: Vec<TreasureType> = Vec::new();
 
@@ -41,7 +42,7 @@ impl G {
         let mut bailout = false;
 
         while !done {
-            werase(w);
+            self.ui.erase(w);
 
             self.wcon(w, G::A_TITLE());
             self.mvwprintw_center(w, 2, "What do you want to sell?\n\n");
@@ -66,15 +67,21 @@ impl G {
 
                 self.wprintw_center_notrim(w, &format!("{:<22}{:>8}\n", tname, gps));
 
-                wattr_set(w, 0, 0);
+                self.ui.attr_clear(w);
             }
 
-            self.mvwprintw_center(w, height - 3, "|[N]|othing");
+            self.mvwprintw_center(
+                w,
+                height - 3,
+                &self
+                    .keys
+                    .menuitem(KeyContext::Vendor, Action::Nothing, "Nothing"),
+            );
 
-            box_(w, 0, 0);
-            wrefresh(w);
+            self.ui.border(w);
+            self.ui.refresh(w);
 
-            let ch = getch();
+            let ch = self.getkey();
 
             if ch == 27 {
                 done = true;
@@ -82,25 +89,28 @@ impl G {
             }
 
             let nch = G::norm_key(ch);
-            match nch {
-                '1'..='9' => {
-                    let index = (nch.to_digit(10).unwrap() - 1) as usize;
-                    if index < treasures.len() {
-                        let tt = treasures[index];
-                        if !sold.contains(&tt) {
-                            if let Err(err) = self.game.vendor_treasure_accept(tt) {
-                                panic!("{:?}", err);
-                            }
-                            sold.push(tt);
+
+            if self.keys.action(KeyContext::Vendor, nch) == Some(Action::Nothing) {
+                done = true;
+                continue;
+            }
+
+            if let '1'..='9' = nch {
+                let index = (nch.to_digit(10).unwrap() - 1) as usize;
+                if index < treasures.len() {
+                    let tt = treasures[index];
+                    if !sold.contains(&tt) {
+                        if let Err(err) = self.game.vendor_treasure_accept(tt) {
+                            panic!("{:?}", err);
                         }
+                        self.tally.gold(pricemap[&tt]);
+                        sold.push(tt);
                     }
                 }
-                'N' => done = true,
-                _ => (),
             }
         }
 
-        G::popup_close(w);
+        self.popup_close(w);
 
         self.redraw_underwins();
 
@@ -108,51 +118,59 @@ impl G {
     }
 
     /// Print armor name and price
-    fn print_armor_prompt(&self, w: WINDOW, armor_type: ArmorType) {
-        let name = match armor_type {
-            ArmorType::Leather => "|[L]|eather",
-            ArmorType::Chainmail => "|[C]|hainmail",
-            ArmorType::Plate => "|[P]|late",
+    fn print_armor_prompt(&self, w: Win, armor_type: ArmorType) {
+        let action = match armor_type {
+            ArmorType::Leather => Action::Leather,
+            ArmorType::Chainmail => Action::Chainmail,
+            ArmorType::Plate => Action::Plate,
             t => panic!("invalid armor type {:#?}", t),
         };
 
+        let name = self
+            .keys
+            .menuitem(KeyContext::Vendor, action, &G::armor_name(armor_type));
+
         let costgp = Armor::cost(armor_type, true);
 
         let cost = format!("{} GP", costgp);
 
         if costgp > self.game.player_gp() {
-            wattr_on(w, self.wcget("dim-red"));
+            self.ui.attr_on(w, self.wcget("dim-red"));
         }
 
         self.wprintw_center(w, &format!("{:<16}{:>8}", name, cost));
 
-        wattr_off(w, self.wcget("dim-red"));
+        self.ui.attr_off(w, self.wcget("dim-red"));
 
-        let _ = waddstr(w, "\n");
+        self.ui.addstr(w, "\n");
     }
 
     /// Print weapon name and price
-    fn print_weapon_prompt(&self, w: WINDOW, weapon_type: WeaponType) {
-        let name = match weapon_type {
-            WeaponType::Dagger => "|[D]|agger",
-            WeaponType::Mace => "|[M]|ace",
-            WeaponType::Sword => "|[S]|word",
+    fn print_weapon_prompt(&self, w: Win, weapon_type: WeaponType) {
+        let action = match weapon_type {
+            WeaponType::Dagger => Action::Dagger,
+            WeaponType::Mace => Action::Mace,
+            WeaponType::Sword => Action::Sword,
             t => panic!("invalid weapon type {:#?}", t),
         };
 
+        let name = self
+            .keys
+            .menuitem(KeyContext::Vendor, action, &G::weapon_name(weapon_type));
+
         let costgp = Weapon::cost(weapon_type, true);
 
         let cost = format!("{} GP", costgp);
 
         if costgp > self.game.player_gp() {
-            wattr_on(w, self.wcget("dim-red"));
+            self.ui.attr_on(w, self.wcget("dim-red"));
         }
 
         self.wprintw_center(w, &format!("{:<16}{:>8}", name, cost));
 
-        wattr_off(w, self.wcget("dim-red"));
+        self.ui.attr_off(w, self.wcget("dim-red"));
 
-        let _ = waddstr(w, "\n");
+        self.ui.addstr(w, "\n");
     }
 
     /// Warn the user that they're about to purchase something they already have
@@ -161,13 +179,13 @@ impl G {
         let title = format!("You already have {}{}!", purchase_type, or_better_msg);
         let width = if downgrade { title.len() + 10 } else { 45 };
 
-        let w = G::popup(9, width as i32);
+        let w = self.popup(9, width as i32);
 
         self.wcon(w, G::A_WARN_TITLE());
         self.mvwprintw_center(w, 2, &title);
         self.wcoff(w, G::A_WARN_TITLE());
 
-        let _ = waddstr(w, "\n\n");
+        self.ui.addstr(w, "\n\n");
 
         if downgrade {
             self.wprintw_center(w, "Are you sure you want to buy this?");
@@ -175,16 +193,16 @@ impl G {
             self.wprintw_center(w, "Do you really want to buy it again?");
         }
 
-        let _ = waddstr(w, "\n\n");
+        self.ui.addstr(w, "\n\n");
 
         self.wprintw_center(w, "|[Y]|es   |[N]|o");
 
-        box_(w, 0, 0);
-        wrefresh(w);
+        self.ui.border(w);
+        self.ui.refresh(w);
 
-        let yes = G::norm_key(getch()) == 'Y';
+        let yes = G::norm_key(self.getkey()) == 'Y';
 
-        G::popup_close(w);
+        self.popup_close(w);
 
         yes
     }
@@ -233,17 +251,17 @@ impl G {
     }
 
     /// Trade equipment
-    fn trade_equipment(&mut self) -> bool {
+    pub fn trade_equipment(&mut self) -> bool {
         let mut bailout = false;
 
         let height = 20;
 
-        let w = G::popup(height, 47);
+        let w = self.popup(height, 47);
 
         let mut done = false;
 
         while !done {
-            werase(w);
+            self.ui.erase(w);
 
             self.wcon(w, G::A_TITLE());
             self.mvwprintw_center(w, 2, "What equipment would you like to buy?\n\n");
@@ -260,62 +278,68 @@ impl G {
             }
 
             self.wprintw_center(w, &cur_equip);
-            let _ = waddstr(w, "\n");
+            self.ui.addstr(w, "\n");
             self.wprintw_center(w, &format!("and {} GPs", self.game.player_gp()));
 
-            let _ = waddstr(w, "\n\n");
+            self.ui.addstr(w, "\n\n");
 
             self.print_armor_prompt(w, ArmorType::Leather);
             self.print_armor_prompt(w, ArmorType::Chainmail);
             self.print_armor_prompt(w, ArmorType::Plate);
 
-            let _ = waddstr(w, "\n");
+            self.ui.addstr(w, "\n");
 
             self.print_weapon_prompt(w, WeaponType::Dagger);
             self.print_weapon_prompt(w, WeaponType::Mace);
             self.print_weapon_prompt(w, WeaponType::Sword);
 
-            let _ = waddstr(w, "\n");
+            self.ui.addstr(w, "\n");
 
             {
-                let name = "L|[a]|mp";
+                let name = self.keys.menuitem(KeyContext::Vendor, Action::Lamp, "Lamp");
                 let cost = format!("{} GP", Game::vendor_lamp_cost());
 
                 if !self.game.vendor_can_afford_lamp() {
-                    wattr_on(w, self.wcget("dim-red"));
+                    self.ui.attr_on(w, self.wcget("dim-red"));
                 }
 
                 self.wprintw_center(w, &format!("{:<16}{:>8}\n", name, cost));
 
-                wattr_off(w, self.wcget("dim-red"));
+                self.ui.attr_off(w, self.wcget("dim-red"));
             }
 
-            self.mvwprintw_center(w, height - 3, "|[N]|othing");
+            self.mvwprintw_center(
+                w,
+                height - 3,
+                &self
+                    .keys
+                    .menuitem(KeyContext::Vendor, Action::Nothing, "Nothing"),
+            );
 
-            box_(w, 0, 0);
-            wrefresh(w);
+            self.ui.border(w);
+            self.ui.refresh(w);
 
-            let ch = getch();
+            let ch = self.getkey();
 
             if ch == 27 {
                 done = true;
                 bailout = true;
             }
 
-            match G::norm_key(ch) {
-                'N' => done = true,
-                'L' => self.purchase_armor(ArmorType::Leather),
-                'C' => self.purchase_armor(ArmorType::Chainmail),
-                'P' => self.purchase_armor(ArmorType::Plate),
-                'D' => self.purchase_weapon(WeaponType::Dagger),
-                'M' => self.purchase_weapon(WeaponType::Mace),
-                'S' => self.purchase_weapon(WeaponType::Sword),
-                'A' => self.purchase_lamp(),
+            match self.keys.action(KeyContext::Vendor, G::norm_key(ch)) {
+                Some(Action::Nothing) => done = true,
+                Some(Action::Leather) => self.purchase_armor(ArmorType::Leather),
+                Some(Action::Chainmail) => self.purchase_armor(ArmorType::Chainmail),
+                Some(Action::Plate) => self.purchase_armor(ArmorType::Plate),
+                Some(Action::Dagger) => self.purchase_weapon(WeaponType::Dagger),
+                Some(Action::Mace) => self.purchase_weapon(WeaponType::Mace),
+                Some(Action::Sword) => self.purchase_weapon(WeaponType::Sword),
+                Some(Action::Lamp) => self.purchase_lamp(),
                 _ => (),
             }
         }
 
-        G::popup_close(w);
+        self.popup_close(w);
 
         self.redraw_underwins();
 
@@ -334,25 +358,29 @@ impl G {
     }
 
     /// Print out a stat
-    fn print_stat_prompt(&self, w: WINDOW, stat: Stat) {
+    fn print_stat_prompt(&self, w: Win, stat: Stat) {
         if !self.game.vendor_can_afford_stat() {
-            wattr_on(w, self.wcget("dim-red"));
+            self.ui.attr_on(w, self.wcget("dim-red"));
         } else if self.game.player_stat_maxed(stat) {
-            wattr_on(w, self.wcget("dim-green"));
+            self.ui.attr_on(w, self.wcget("dim-green"));
         };
 
-        let prompt = match stat {
-            Stat::Strength => "|[S]|trength",
-            Stat::Intelligence => "|[I]|ntelligence",
-            Stat::Dexterity => "|[D]|exterity",
+        let action = match stat {
+            Stat::Strength => Action::Strength,
+            Stat::Intelligence => Action::Intelligence,
+            Stat::Dexterity => Action::Dexterity,
         };
 
+        let prompt = self
+            .keys
+            .menuitem(KeyContext::Potions, action, &G::stat_name(stat));
+
         let cost = format!("{} GP", Game::vendor_stat_cost());
 
         self.wprintw_center(w, &format!("{:<17}  {}\n", prompt, cost));
 
-        wattr_off(w, self.wcget("dim-red"));
-        wattr_off(w, self.wcget("dim-green"));
+        self.ui.attr_off(w, self.wcget("dim-red"));
+        self.ui.attr_off(w, self.wcget("dim-green"));
     }
 
     /// Trade for potions
@@ -363,12 +391,12 @@ impl G {
 
         let height = 14;
 
-        let w = G::popup(height, 41);
+        let w = self.popup(height, 41);
 
         let mut done = false;
 
         while !done {
-            werase(w);
+            self.ui.erase(w);
 
             self.wcon(w, G::A_TITLE());
             self.mvwprintw_center(w, 2, "Would you like to buy some potions?\n\n");
@@ -390,27 +418,33 @@ impl G {
             self.print_stat_prompt(w, Stat::Intelligence);
             self.print_stat_prompt(w, Stat::Dexterity);
 
-            self.mvwprintw_center(w, height - 3, "|[N]|othing");
+            self.mvwprintw_center(
+                w,
+                height - 3,
+                &self
+                    .keys
+                    .menuitem(KeyContext::Potions, Action::Nothing, "Nothing"),
+            );
 
-            box_(w, 0, 0);
-            wrefresh(w);
+            self.ui.border(w);
+            self.ui.refresh(w);
 
-            let ch = getch();
+            let ch = self.getkey();
 
             if ch == 27 {
                 done = true;
             }
 
-            match G::norm_key(ch) {
-                'S' => self.buy_stat(Stat::Strength),
-                'I' => self.buy_stat(Stat::Intelligence),
-                'D' => self.buy_stat(Stat::Dexterity),
-                'N' => done = true,
+            match self.keys.action(KeyContext::Potions, G::norm_key(ch)) {
+                Some(Action::Strength) => self.buy_stat(Stat::Strength),
+                Some(Action::Intelligence) => self.buy_stat(Stat::Intelligence),
+                Some(Action::Dexterity) => self.buy_stat(Stat::Dexterity),
+                Some(Action::Nothing) => done = true,
                 _ => (),
             }
         }
 
-        G::popup_close(w);
+        self.popup_close(w);
         self.redraw_underwins();
     }
 
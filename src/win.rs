@@ -1,12 +1,24 @@
 /// Windows-related functions
 use crate::G;
-use ncurses::*;
+use crate::frontend::*;
 
 use std::collections::HashMap;
 
+use wizardscastle::game::Direction;
+
+/// The smallest screen everything fits on
+pub const MIN_LINES: i32 = 25;
+pub const MIN_COLS: i32 = 80;
+
+/// Map and status window sizes. The log takes the rest of the screen below
+/// them.
+pub const MAP_COLS: i32 = 47;
+pub const STAT_COLS: i32 = 32;
+pub const LOG_Y: i32 = 17;
+
 impl G {
     /// Look up a color attribute by the given name
-    pub fn wcget(&self, c: &str) -> u32 {
+    pub fn wcget(&self, c: &str) -> Attr {
         if let Some(attr) = self.color.get(c) {
             *attr
         } else {
@@ -19,35 +31,35 @@ impl G {
     /// See `new()` for a list of attributes.
     ///
     /// You probably want `wcon()` or `wcoff()` instead.
-    pub fn wcset(&self, w: WINDOW, c: &str, on: bool) {
+    pub fn wcset(&self, w: Win, c: &str, on: bool) {
         let attr = self.wcget(c);
 
         if on {
-            wattr_on(w, attr);
+            self.ui.attr_on(w, attr);
         } else {
-            wattr_off(w, attr);
+            self.ui.attr_off(w, attr);
         }
     }
 
     /// Turn on an attribute by name.
-    pub fn wcon(&self, w: WINDOW, c: &str) {
+    pub fn wcon(&self, w: Win, c: &str) {
         self.wcset(w, c, true);
     }
 
     /// Turn off an attribute by name.
-    pub fn wcoff(&self, w: WINDOW, c: &str) {
+    pub fn wcoff(&self, w: Win, c: &str) {
         self.wcset(w, c, false);
     }
 
     /// Print some text in the center of a window
-    pub fn wprintw_center(&self, w: WINDOW, s: &str) {
-        self.mvwprintw_center(w, getcury(w), s)
+    pub fn wprintw_center(&self, w: Win, s: &str) {
+        self.mvwprintw_center(w, self.ui.cury(w), s)
     }
 
     /// Print some text in the center of a window, ignoring leading and trailing
     /// whitespace.
-    pub fn wprintw_center_notrim(&self, w: WINDOW, s: &str) {
-        self.mvwprintw_center_notrim(w, getcury(w), s)
+    pub fn wprintw_center_notrim(&self, w: Win, s: &str) {
+        self.mvwprintw_center_notrim(w, self.ui.cury(w), s)
     }
 
     /// Print some text in the center of a window, ignoring leading and trailing
@@ -65,12 +77,12 @@ impl G {
     ///   %b  toggle bold
     ///   %D  toggle bold-red
     ///   %Y  toggle bold-yellow
-    pub fn mvwprintw_center(&self, w: WINDOW, y: i32, s: &str) {
+    pub fn mvwprintw_center(&self, w: Win, y: i32, s: &str) {
         self.mvwprintw_center_core(w, y, s, true)
     }
 
     /// Variant doesn't trim before centering
-    pub fn mvwprintw_center_notrim(&self, w: WINDOW, y: i32, s: &str) {
+    pub fn mvwprintw_center_notrim(&self, w: Win, y: i32, s: &str) {
         self.mvwprintw_center_core(w, y, s, false)
     }
 
@@ -90,7 +102,7 @@ impl G {
     }
 
     /// Base functionality
-    fn mvwprintw_center_core(&self, w: WINDOW, y: i32, s: &str, trim: bool) {
+    fn mvwprintw_center_core(&self, w: Win, y: i32, s: &str, trim: bool) {
         let mut state_map = HashMap::new();
 
         state_map.insert('r', false); // reverse
@@ -125,9 +137,9 @@ impl G {
             len += 1;
         }
 
-        let x = (getmaxx(w) - len) / 2;
+        let x = (self.ui.maxx(w) - len) / 2;
 
-        wmove(w, y, x);
+        self.ui.mv(w, y, x);
 
         check_next = false;
 
@@ -139,16 +151,16 @@ impl G {
                     match c {
                         'r' => {
                             if on {
-                                wattr_on(w, A_REVERSE);
+                                self.ui.attr_on(w, A_REVERSE);
                             } else {
-                                wattr_off(w, A_REVERSE);
+                                self.ui.attr_off(w, A_REVERSE);
                             }
                         }
                         'b' => {
                             if on {
-                                wattr_on(w, A_BOLD);
+                                self.ui.attr_on(w, A_BOLD);
                             } else {
-                                wattr_off(w, A_BOLD);
+                                self.ui.attr_off(w, A_BOLD);
                             }
                         }
                         'D' => {
@@ -178,54 +190,282 @@ impl G {
                 let reversed = G::invert_state(&mut state_map, 'r');
 
                 if reversed {
-                    wattr_on(w, A_REVERSE);
+                    self.ui.attr_on(w, A_REVERSE);
                 } else {
-                    wattr_off(w, A_REVERSE);
+                    self.ui.attr_off(w, A_REVERSE);
                 }
                 continue;
             }
 
-            let _ = waddch(w, c as chtype);
+            self.ui.addch(w, c);
         }
     }
 
     /// Pop-up a new window of a given size with a border.
-    pub fn popup(lines: i32, cols: i32) -> WINDOW {
-        let x = (COLS() - cols) / 2;
+    pub fn popup(&self, lines: i32, cols: i32) -> Win {
+        let (y, x) = self.popup_pos(lines, cols);
+
+        let w = self.ui.newwin(lines, cols, y, x);
+
+        self.popups.borrow_mut().push((w, lines, cols));
+
+        w
+    }
+
+    /// Change the size of a popup, like one that fills the screen after the
+    /// screen changes size
+    pub fn resize_popup(&self, w: Win, lines: i32, cols: i32) {
+        if let Some(p) = self.popups.borrow_mut().iter_mut().find(|p| p.0 == w) {
+            p.1 = lines;
+            p.2 = cols;
+        }
+
+        let (y, x) = self.popup_pos(lines, cols);
+
+        self.ui.resize(w, lines, cols);
+        self.ui.mvwin(w, y, x);
+    }
+
+    /// Where a popup goes on the screen
+    fn popup_pos(&self, lines: i32, cols: i32) -> (i32, i32) {
+        let x = (self.ui.cols() - cols) / 2;
 
         // Aesthetic positioning logic
-        let mut y = if LINES() <= 25 && lines >= 12 {
-            (LINES() as f32 / 2.5) as i32 - lines / 2
+        let mut y = if self.ui.lines() <= 25 && lines >= 12 {
+            (self.ui.lines() as f32 / 2.5) as i32 - lines / 2
         } else {
-            LINES() / 4 - lines / 2
+            self.ui.lines() / 4 - lines / 2
         };
 
         if y < 0 {
             y = 0;
         }
 
-        newwin(lines, cols, y, x)
+        (y, x)
     }
 
     /// Close a popup
-    pub fn popup_close(w: WINDOW) {
-        werase(w);
-        wrefresh(w);
-        delwin(w);
+    pub fn popup_close(&self, w: Win) {
+        self.ui.erase(w);
+        self.ui.refresh(w);
+        self.ui.delwin(w);
+
+        self.popups.borrow_mut().retain(|p| p.0 != w);
     }
 
-    /// Show or hide the cursor.
-    pub fn show_cursor(show: bool) {
-        if show {
-            curs_set(CURSOR_VISIBILITY::CURSOR_VISIBLE);
-        } else {
-            curs_set(CURSOR_VISIBILITY::CURSOR_INVISIBLE);
+    /// Show a list of lines in a popup, scrolling with Up and Down if they
+    /// don't all fit, until any other key
+    ///
+    /// The lines are printed as is, so they can hold text the player typed.
+    pub fn list_popup(&self, title: &str, empty: &str, rows: &[String]) {
+        let lines = self.ui.lines();
+
+        let page = (lines - 10).max(1) as usize;
+        let height = rows.len().clamp(1, page) as i32 + 8;
+
+        let w = self.popup(height, 70);
+
+        let mut top = 0;
+
+        loop {
+            self.ui.erase(w);
+
+            self.wcon(w, G::A_TITLE());
+            self.mvwprintw_center(w, 2, title);
+            self.wcoff(w, G::A_TITLE());
+
+            if rows.is_empty() {
+                self.mvwprintw_center(w, 4, empty);
+            }
+
+            for (i, r) in rows.iter().skip(top).take(page).enumerate() {
+                self.ui.mvaddstr(w, 4 + i as i32, 4, r);
+            }
+
+            let more = if rows.len() > page {
+                " Up/Down scroll, any other key closes "
+            } else {
+                " Press any key "
+            };
+
+            self.ui.attr_on(w, A_REVERSE);
+            self.mvwprintw_center_notrim(w, height - 3, more);
+            self.ui.attr_off(w, A_REVERSE);
+
+            self.ui.border(w);
+            self.ui.refresh(w);
+
+            let key = self.getkey();
+
+            match self.key_dir(key) {
+                Some(Direction::North) => top = top.saturating_sub(1),
+                Some(Direction::South) => top = (top + 1).min(rows.len().saturating_sub(page)),
+                _ => break,
+            }
         }
+
+        self.popup_close(w);
+
+        self.redraw_underwins();
+    }
+
+    /// Show a "terminal too small" screen until the terminal is big enough
+    pub fn wait_for_size(ui: &dyn Frontend) {
+        while ui.lines() < MIN_LINES || ui.cols() < MIN_COLS {
+            let lines = ui.lines();
+            let cols = ui.cols();
+
+            let w = ui.newwin(lines, cols, 0, 0);
+
+            let msgs = [
+                String::from("Your terminal is too small!"),
+                format!(
+                    "It's {}x{}, but it needs to be at least {}x{}.",
+                    cols, lines, MIN_COLS, MIN_LINES
+                ),
+                String::from("Make it bigger to keep playing."),
+            ];
+
+            for (i, m) in msgs.iter().enumerate() {
+                let x = ((cols - m.len() as i32) / 2).max(0);
+
+                ui.mvaddstr(w, lines / 2 - 2 + i as i32 * 2, x, m);
+            }
+
+            ui.refresh(w);
+
+            while ui.getch(-1) != Some(KEY_RESIZE) {}
+
+            ui.delwin(w);
+        }
+    }
+
+    /// Put the main windows where they go for the current screen size
+    ///
+    /// If there's room to the right, the atlas goes there and the log
+    /// stops short of it.
+    pub fn layout(&self) {
+        let lines = self.ui.lines();
+        let cols = self.ui.cols();
+
+        let atlas_cols = cols - MIN_COLS;
+        let atlas = self.atlas_across(lines, atlas_cols).is_some();
+
+        match (self.atlaswin.get(), atlas) {
+            (None, true) => {
+                let w = self.ui.newwin(lines, atlas_cols, 0, MIN_COLS);
+                self.atlaswin.set(Some(w));
+            }
+            (Some(w), true) => {
+                self.ui.resize(w, lines, atlas_cols);
+                self.ui.mvwin(w, 0, MIN_COLS);
+            }
+            (Some(w), false) => {
+                self.ui.delwin(w);
+                self.atlaswin.set(None);
+            }
+            (None, false) => (),
+        }
+
+        let log_cols = if atlas { MIN_COLS } else { cols };
+
+        self.ui.resize(self.mapwin, LOG_Y, MAP_COLS);
+        self.ui.mvwin(self.mapwin, 0, 0);
+
+        self.ui.resize(self.statwin, LOG_Y, STAT_COLS);
+        self.ui.mvwin(self.statwin, 0, MAP_COLS + 1);
+
+        self.ui.resize(self.logwin, lines - LOG_Y, log_cols);
+        self.ui
+            .resize(self.loginner, lines - LOG_Y - 1, log_cols - 2);
+        self.ui.mvwin(self.logwin, LOG_Y, 0);
+    }
+
+    /// Lay the screen out again after the terminal changes size
+    pub fn handle_resize(&self) {
+        G::wait_for_size(&*self.ui);
+
+        self.ui.clear();
+
+        self.layout();
+
+        if self.in_castle {
+            self.redraw_log();
+            self.update_map(self.options.discover_all);
+            self.update_stat();
+        }
+
+        for &(w, lines, cols) in self.popups.borrow().iter() {
+            let (y, x) = self.popup_pos(lines, cols);
+
+            self.ui.resize(w, lines, cols);
+            self.ui.mvwin(w, y, x);
+            self.ui.touch(w);
+            self.ui.refresh(w);
+        }
+    }
+
+    /// Get a key from the player
+    ///
+    /// If the terminal changes size while waiting, the screen is laid out
+    /// again.
+    pub fn getkey(&self) -> i32 {
+        // Whatever's in the log has had its chance to be read
+        self.log_unseen.set(0);
+
+        loop {
+            match self.ui.getch(-1) {
+                Some(KEY_RESIZE) => self.handle_resize(),
+                Some(k) => break k,
+                None => (),
+            }
+        }
+    }
+
+    /// See if the player pressed a key to stop something that goes on by
+    /// itself, waiting up to `delay` milliseconds for one
+    ///
+    /// The key itself is thrown away.
+    pub fn poll_interrupt(&self, delay: i32) -> bool {
+        match self.ui.getch(delay) {
+            None => false,
+            Some(KEY_RESIZE) => {
+                self.handle_resize();
+                false
+            }
+            Some(_) => true,
+        }
+    }
+
+    /// Read a line of up to `n` characters at the cursor, echoing as it goes
+    pub fn read_line(&self, w: Win, n: usize) -> String {
+        let y = self.ui.cury(w);
+        let x = self.ui.curx(w);
+
+        let mut s = String::new();
+
+        loop {
+            self.ui
+                .mvaddstr(w, y, x, &format!("{:<width$}", s, width = n));
+            self.ui.mv(w, y, x + s.len() as i32);
+            self.ui.refresh(w);
+
+            match self.getkey() {
+                10 | 13 | KEY_ENTER => break,
+                8 | 127 | KEY_BACKSPACE => {
+                    s.pop();
+                }
+                k if (32..127).contains(&k) && s.len() < n => s.push(k as u8 as char),
+                _ => (),
+            }
+        }
+
+        s
     }
 
     /*
     /// Clear the border
-    pub fn clear_border(w: WINDOW) {
+    pub fn clear_border(w: Win) {
         let ch = ' ' as chtype;
         wborder(w, ch, ch, ch, ch, ch, ch, ch, ch);
     }
@@ -236,7 +476,7 @@ impl G {
 
         width += width % 2; // Force to even width
 
-        let w = G::popup(9, width);
+        let w = self.popup(9, width);
 
         self.wcon(w, "bold-red");
         self.mvwprintw_center(w, 2, &format!("** SILLY {} **", self.player_race_name()));
@@ -244,20 +484,20 @@ impl G {
 
         self.mvwprintw_center(w, 4, s);
 
-        wattron(w, A_REVERSE);
+        self.ui.attr_on(w, A_REVERSE);
         self.mvwprintw_center(w, 6, " Press any key ");
-        wattroff(w, A_REVERSE);
+        self.ui.attr_off(w, A_REVERSE);
 
         self.wcon(w, "bold-red");
-        wattr_on(w, A_REVERSE);
-        box_(w, 0, 0);
-        wattr_off(w, A_REVERSE);
+        self.ui.attr_on(w, A_REVERSE);
+        self.ui.border(w);
+        self.ui.attr_off(w, A_REVERSE);
         self.wcoff(w, "bold-red");
 
-        wrefresh(w);
+        self.ui.refresh(w);
 
-        getch();
+        self.getkey();
 
-        G::popup_close(w);
+        self.popup_close(w);
     }
 }